@@ -0,0 +1,82 @@
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn sextet(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_base64(data: &str) -> bool {
+    data.bytes().all(|b| b == b'=' || sextet(b).is_some())
+}
+
+//An upper bound on the length of the decoded data.
+pub(crate) fn decoded_len(data: &str) -> usize {
+    data.len() / 4 * 3 + 2
+}
+
+//Decodes base64 with or without padding into `out`, returning how many bytes were written.
+pub(crate) fn decode_into(data: &str, out: &mut [u8]) -> Option<usize> {
+    let unpadded = data.trim_end_matches('=');
+    let padding = data.len() - unpadded.len();
+    if unpadded.len() % 4 == 1 || padding > 2 || (padding > 0 && unpadded.len() % 4 + padding != 4)
+    {
+        return None;
+    }
+
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut len = 0;
+    for byte in unpadded.bytes() {
+        acc = (acc << 6) | u32::from(sextet(byte)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            *out.get_mut(len)? = (acc >> bits) as u8;
+            len += 1;
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(len)
+}
+
+#[cfg(any(feature = "std", test))]
+pub(crate) fn decode(data: &str) -> Option<std::vec::Vec<u8>> {
+    let mut out = std::vec![0; decoded_len(data)];
+    let len = decode_into(data, &mut out)?;
+    out.truncate(len);
+    Some(out)
+}
+
+///Writes bytes as padded base64, for building the payload of a `Clipboard` or any of the other
+///sequences that carry binary data.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Base64<'a>(pub &'a [u8]);
+
+impl<'a> Display for Base64<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        use core::fmt::Write;
+
+        for chunk in self.0.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let acc = u32::from(group[0]) << 16 | u32::from(group[1]) << 8 | u32::from(group[2]);
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let idx = (acc >> (18 - 6 * i)) & 0x3f;
+                    formatter.write_char(ALPHABET[idx as usize] as char)?;
+                } else {
+                    formatter.write_char('=')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
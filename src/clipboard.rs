@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests;
+
+use crate::base64;
+
+///One of the selections an OSC 52 sequence can target.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Selection {
+    ///`c`
+    Clipboard,
+    ///`p`
+    Primary,
+    ///`q`
+    Secondary,
+    ///`s`, which terminals map to one of the others.
+    Select,
+    ///`0` to `7`
+    CutBuffer(u8),
+}
+
+impl Selection {
+    fn from_char(ch: char) -> Option<Selection> {
+        match ch {
+            'c' => Some(Selection::Clipboard),
+            'p' => Some(Selection::Primary),
+            'q' => Some(Selection::Secondary),
+            's' => Some(Selection::Select),
+            '0'..='7' => Some(Selection::CutBuffer(ch as u8 - b'0')),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn is_selection(ch: char) -> bool {
+    Selection::from_char(ch).is_some()
+}
+
+pub(crate) fn is_clipboard_data(data: &str) -> bool {
+    data == "?" || base64::is_base64(data)
+}
+
+///The contents of an OSC 52 sequence, which reads or writes the clipboard.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Clipboard<'a> {
+    ///The targeted selections. When empty, terminals use `s0`.
+    pub selection: &'a str,
+    ///`?` to ask for the contents of the selection, otherwise the contents in base64. Empty data
+    ///clears the selection.
+    pub data: &'a str,
+}
+
+impl<'a> Clipboard<'a> {
+    ///The targeted selections, in the order they were given.
+    pub fn targets(&self) -> impl Iterator<Item = Selection> + 'a {
+        self.selection.chars().filter_map(Selection::from_char)
+    }
+
+    ///Whether this asks the terminal for the contents, rather than setting them. The terminal
+    ///answers with the same sequence, carrying the contents.
+    pub fn is_query(&self) -> bool {
+        self.data == "?"
+    }
+
+    ///An upper bound on the length of the decoded data.
+    pub fn decoded_len(&self) -> usize {
+        if self.is_query() {
+            0
+        } else {
+            base64::decoded_len(self.data)
+        }
+    }
+
+    ///Decodes the data into `out`, returning how many bytes were written. This fails on a
+    ///query, malformed base64, or if `out` is too short.
+    pub fn decode_into(&self, out: &mut [u8]) -> Option<usize> {
+        if self.is_query() {
+            return None;
+        }
+        base64::decode_into(self.data, out)
+    }
+
+    ///Decodes the data. This fails on a query or malformed base64.
+    #[cfg(any(feature = "std", test))]
+    pub fn decode(&self) -> Option<std::vec::Vec<u8>> {
+        if self.is_query() {
+            return None;
+        }
+        base64::decode(self.data)
+    }
+}
@@ -0,0 +1,52 @@
+use crate::*;
+
+fn clip(data: &str) -> Clipboard<'_> {
+    Clipboard {
+        selection: "c",
+        data,
+    }
+}
+
+#[test]
+fn test_base64() {
+    let cases: [(&[u8], &str); 7] = [
+        (b"", ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"foob", "Zm9vYg=="),
+        (b"fooba", "Zm9vYmE="),
+        (b"\xff\xfe\x00", "//4A"),
+    ];
+    for (raw, encoded) in cases.iter() {
+        assert_eq!(format!("{}", Base64(raw)), *encoded);
+        assert_eq!(clip(encoded).decode().as_deref(), Some(*raw));
+    }
+
+    // Padding may be left off.
+    assert_eq!(clip("Zm9vYg").decode().unwrap(), b"foob");
+
+    let mut short = [0; 2];
+    assert_eq!(clip("Zm9v").decode_into(&mut short), None);
+    assert_eq!(clip("Zm9vY").decode(), None);
+    assert_eq!(clip("Zg=").decode(), None);
+    assert_eq!(clip("Zg=a").decode(), None);
+    assert_eq!(clip("?").decode(), None);
+}
+
+#[test]
+fn test_targets() {
+    let clip = Clipboard {
+        selection: "cp7",
+        data: "?",
+    };
+    assert!(clip.is_query());
+    assert_eq!(
+        clip.targets().collect::<Vec<_>>(),
+        vec![
+            Selection::Clipboard,
+            Selection::Primary,
+            Selection::CutBuffer(7)
+        ]
+    );
+}
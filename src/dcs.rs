@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests;
+
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+
+fn nibble(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn is_hex(s: &str) -> bool {
+    !s.is_empty() && s.len() & 1 == 0 && s.bytes().all(|b| nibble(b).is_some())
+}
+
+///A hex encoded string, as used by XTGETTCAP. Always made up of an even number of hex digits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HexStr<'a>(&'a str);
+
+impl<'a> HexStr<'a> {
+    ///The string as it was written.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    ///The bytes this stands for.
+    pub fn decode(&self) -> impl Iterator<Item = u8> + 'a {
+        self.0.as_bytes().chunks(2).map(|pair| {
+            let hi = nibble(pair[0]).unwrap_or(0);
+            let lo = nibble(pair[1]).unwrap_or(0);
+            hi << 4 | lo
+        })
+    }
+
+    ///Whether this decodes to `text`.
+    pub fn is(&self, text: &str) -> bool {
+        self.decode().eq(text.bytes())
+    }
+}
+
+impl<'a> Display for HexStr<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+///Writes bytes as upper case hex, for building an XTGETTCAP request.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl<'a> Display for Hex<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        for byte in self.0 {
+            write!(formatter, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+///The `;` separated capabilities of an XTGETTCAP request or reply. A request only has names,
+///a reply pairs each name with its value, as `name=value`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Termcaps<'a>(&'a str);
+
+impl<'a> Termcaps<'a> {
+    ///Checks that `data` is made up of hex encoded names, each optionally followed by `=` and
+    ///a hex encoded value.
+    pub fn new(data: &'a str) -> Option<Termcaps<'a>> {
+        let valid = data.split(';').all(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let name = parts.next().unwrap_or("");
+            match parts.next() {
+                Some(value) => is_hex(name) && (value.is_empty() || is_hex(value)),
+                None => is_hex(name),
+            }
+        });
+        if valid {
+            Some(Termcaps(data))
+        } else {
+            None
+        }
+    }
+
+    ///The capabilities as they were written.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    pub fn has_values(&self) -> bool {
+        self.0.contains('=')
+    }
+
+    ///The names of the capabilities, along with their values if there are any.
+    pub fn entries(&self) -> impl Iterator<Item = (HexStr<'a>, Option<HexStr<'a>>)> + 'a {
+        self.0.split(';').map(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let name = HexStr(parts.next().unwrap_or(""));
+            (name, parts.next().map(HexStr))
+        })
+    }
+
+    ///The value of the capability called `name`, if it was given.
+    pub fn get(&self, name: &str) -> Option<HexStr<'a>> {
+        self.entries()
+            .find(|(entry, _)| entry.is(name))
+            .and_then(|(_, value)| value)
+    }
+}
+
+impl<'a> Display for Termcaps<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{}", self.0)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::*;
+
+#[test]
+fn test_termcaps() {
+    let caps = Termcaps::new("544e=787465726d;636f6c6f7273=323536;6b6263").unwrap();
+    assert!(caps.has_values());
+    assert_eq!(
+        caps.get("TN").unwrap().decode().collect::<Vec<_>>(),
+        b"xterm"
+    );
+    assert!(caps.get("colors").unwrap().is("256"));
+    assert_eq!(caps.get("kbc"), None);
+    assert_eq!(caps.get("Co"), None);
+    assert_eq!(caps.entries().count(), 3);
+
+    let request = Termcaps::new("544E").unwrap();
+    assert!(!request.has_values());
+    assert!(request.entries().next().unwrap().0.is("TN"));
+
+    assert_eq!(Termcaps::new(""), None);
+    assert_eq!(Termcaps::new("544"), None);
+    assert_eq!(Termcaps::new("TN"), None);
+    assert_eq!(Termcaps::new("544e;"), None);
+    assert_eq!(Termcaps::new("=787465726d"), None);
+}
+
+#[test]
+fn test_hex() {
+    assert_eq!(format!("{}", Hex(b"TN")), "544E");
+    assert_eq!(format!("{}", Hex(b"")), "");
+    assert_eq!(format!("{}", Hex(&[0, 0xff])), "00FF");
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests;
+
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+
+///A feature listed in a primary device attributes (DA1) reply, after the device class.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DeviceAttribute {
+    ///1
+    Columns132,
+    ///2
+    Printer,
+    ///3
+    ReGis,
+    ///4
+    Sixel,
+    ///6
+    SelectiveErase,
+    ///8
+    UserDefinedKeys,
+    ///9
+    NationalCharsets,
+    ///15
+    TechnicalCharacters,
+    ///16
+    LocatorPort,
+    ///17
+    TerminalStateInterrogation,
+    ///18
+    UserWindows,
+    ///21
+    HorizontalScrolling,
+    ///22
+    AnsiColor,
+    ///28
+    RectangularEditing,
+    ///29
+    AnsiTextLocator,
+    Other(u16),
+    ///An empty code, such as the one kitty ends its reply with. It stands for no feature, and
+    ///is kept so that the reply is written back as it came.
+    Empty,
+}
+
+impl DeviceAttribute {
+    ///The code of the feature. `Empty` has none, and gives 0, which no feature uses.
+    pub fn code(self) -> u16 {
+        use DeviceAttribute::*;
+        match self {
+            Columns132 => 1,
+            Printer => 2,
+            ReGis => 3,
+            Sixel => 4,
+            SelectiveErase => 6,
+            UserDefinedKeys => 8,
+            NationalCharsets => 9,
+            TechnicalCharacters => 15,
+            LocatorPort => 16,
+            TerminalStateInterrogation => 17,
+            UserWindows => 18,
+            HorizontalScrolling => 21,
+            AnsiColor => 22,
+            RectangularEditing => 28,
+            AnsiTextLocator => 29,
+            Other(code) => code,
+            Empty => 0,
+        }
+    }
+}
+
+impl From<u16> for DeviceAttribute {
+    fn from(code: u16) -> Self {
+        use DeviceAttribute::*;
+        match code {
+            1 => Columns132,
+            2 => Printer,
+            3 => ReGis,
+            4 => Sixel,
+            6 => SelectiveErase,
+            8 => UserDefinedKeys,
+            9 => NationalCharsets,
+            15 => TechnicalCharacters,
+            16 => LocatorPort,
+            17 => TerminalStateInterrogation,
+            18 => UserWindows,
+            21 => HorizontalScrolling,
+            22 => AnsiColor,
+            28 => RectangularEditing,
+            29 => AnsiTextLocator,
+            code => Other(code),
+        }
+    }
+}
+
+impl Display for DeviceAttribute {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        match self {
+            DeviceAttribute::Empty => Ok(()),
+            feature => write!(formatter, "{}", feature.code()),
+        }
+    }
+}
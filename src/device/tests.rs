@@ -0,0 +1,17 @@
+use crate::*;
+
+#[test]
+fn test_codes() {
+    for code in 0..100 {
+        assert_eq!(DeviceAttribute::from(code).code(), code);
+    }
+    assert_eq!(DeviceAttribute::from(4), DeviceAttribute::Sixel);
+    assert_eq!(DeviceAttribute::from(5), DeviceAttribute::Other(5));
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(format!("{}", DeviceAttribute::Sixel), "4");
+    assert_eq!(format!("{}", DeviceAttribute::Other(52)), "52");
+    assert_eq!(format!("{}", DeviceAttribute::Empty), "");
+}
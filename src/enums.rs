@@ -1,11 +1,19 @@
 #[cfg(test)]
 mod tests;
 
-use heapless::{consts::U5, Vec};
+use crate::{
+    AnsiMode, Clipboard, ColorSpec, DecMode, DeviceAttribute, DynamicColor, FileUrl, ITermCommand,
+    KittyGraphics, ModeState, PaletteColors, PaletteIndices, ShellMark, Termcaps, VsCodeMark,
+};
+
+use heapless::{
+    consts::{U16, U3, U32, U64},
+    Vec,
+};
 
 ///The following are the implemented ANSI escape sequences. More to be added.
 #[derive(Debug, PartialEq, Clone)]
-pub enum AnsiSequence {
+pub enum AnsiSequence<'a> {
     Escape,
     CursorPos(u32, u32),
     CursorUp(u32),
@@ -14,10 +22,40 @@ pub enum AnsiSequence {
     CursorBackward(u32),
     CursorSave,
     CursorRestore,
-    EraseDisplay,
-    EraseDisplayFromCursor,
-    EraseLine,
-    SetGraphicsMode(Vec<u8, U5>),
+    CursorNextLine(u32),
+    CursorPreviousLine(u32),
+    ///CHA, moving to a column of the current line.
+    CursorColumn(u32),
+    ///HPA, which moves to a column like `CursorColumn` does.
+    CharacterPositionAbsolute(u32),
+    ///VPA, moving to a line while keeping the column.
+    LinePositionAbsolute(u32),
+    ///HPR, which moves forward like `CursorForward` does.
+    CharacterPositionRelative(u32),
+    ///VPR, which moves down like `CursorDown` does.
+    LinePositionRelative(u32),
+    ///CHT, moving forward by a number of tab stops.
+    CursorForwardTab(u32),
+    ///CBT, moving back by a number of tab stops.
+    CursorBackwardTab(u32),
+    EraseInDisplay(EraseMode),
+    EraseInLine(EraseMode),
+    ///DECSED, which leaves characters protected by DECSCA alone.
+    SelectiveEraseInDisplay(EraseMode),
+    ///DECSEL, which leaves characters protected by DECSCA alone.
+    SelectiveEraseInLine(EraseMode),
+    EraseCharacters(u32),
+    InsertCharacters(u32),
+    DeleteCharacters(u32),
+    InsertLines(u32),
+    DeleteLines(u32),
+    ScrollUp(u32),
+    ScrollDown(u32),
+    ///REP, which repeats the character printed last.
+    RepeatCharacter(u32),
+    ///SGR, holding up to 64 parameters, with each sub-parameter counted as one. Sequences with
+    ///more are left uninterpreted.
+    SetGraphicsMode(Vec<SgrParam, U64>),
     SetMode(u8),
     ResetMode(u8),
     HideCursor,
@@ -41,6 +79,45 @@ pub enum AnsiSequence {
     ResetAutoWrap,
     ResetAutoRepeat,
     ResetInterlacing,
+    ///DECSET and DECRST, `CSI ? Pm h` and `CSI ? Pm l`. The flag tells if the modes are set.
+    DecPrivateMode(Vec<DecMode, U16>, bool),
+    ///SM and RM, `CSI Pm h` and `CSI Pm l`. The flag tells if the modes are set.
+    AnsiMode(Vec<AnsiMode, U16>, bool),
+    ///DECRQM, `CSI ? Ps $ p`, asking the terminal for the state of a DEC private mode.
+    RequestDecMode(DecMode),
+    ///DECRQM, `CSI Ps $ p`, asking the terminal for the state of an ANSI mode.
+    RequestAnsiMode(AnsiMode),
+    ///DECRPM, `CSI ? Ps ; Pm $ y`, the reply to `RequestDecMode`.
+    DecModeReport(DecMode, ModeState),
+    ///DECRPM, `CSI Ps ; Pm $ y`, the reply to `RequestAnsiMode`.
+    AnsiModeReport(AnsiMode, ModeState),
+    ///DA1, `CSI c`, asking the terminal what it is and what it supports.
+    RequestPrimaryAttributes,
+    ///`CSI ? Ps ; ... c`, the reply to DA1: the device class, such as 62 for a VT220, followed
+    ///by the supported features. Replies with more than 32 features are left uninterpreted.
+    PrimaryAttributes(u16, Vec<DeviceAttribute, U32>),
+    ///DA2, `CSI > c`, asking for the terminal type and version.
+    RequestSecondaryAttributes,
+    ///`CSI > Pp ; Pv ; Pc c`, the reply to DA2: the terminal type, the firmware version and
+    ///the ROM cartridge number, which is usually 0.
+    SecondaryAttributes(u32, u32, u32),
+    ///DA3, `CSI = c`, asking for the unit id.
+    RequestTertiaryAttributes,
+    ///`DCS ! | id ST`, the reply to DA3, with the unit id in hex.
+    TertiaryAttributes(&'a str, Terminator),
+    ///DSR, `CSI 5 n`, asking whether the terminal is working.
+    RequestDeviceStatus,
+    ///`CSI 0 n` or `CSI 3 n`, the reply to `RequestDeviceStatus`. The flag tells if the
+    ///terminal is ready.
+    DeviceStatusReport(bool),
+    ///DSR, `CSI 6 n`, asking where the cursor is.
+    RequestCursorPosition,
+    ///CPR, `CSI Pl ; Pc R`, the line and column of the cursor.
+    CursorPositionReport(u32, u32),
+    ///XTVERSION, `CSI > q`, asking for the terminal's name and version.
+    RequestTerminalVersion,
+    ///`DCS > | text ST`, the reply to XTVERSION, such as `xterm(367)`.
+    TerminalVersion(&'a str, Terminator),
     SetAlternateKeypad,
     SetNumericKeypad,
     SetUKG0,
@@ -55,11 +132,146 @@ pub enum AnsiSequence {
     SetG1AltAndSpecialGraph,
     SetSingleShift2,
     SetSingleShift3,
+    ///DECSC, `ESC 7`, which saves the attributes and character sets along with the cursor.
+    DecSaveCursor,
+    ///DECRC, `ESC 8`.
+    DecRestoreCursor,
+    ///RIS, `ESC c`.
+    FullReset,
+    ///IND, `ESC D`, moving down a line and scrolling at the bottom margin.
+    Index,
+    ///NEL, `ESC E`.
+    NextLine,
+    ///RI, `ESC M`, moving up a line and scrolling at the top margin.
+    ReverseIndex,
+    ///HTS, `ESC H`, setting a tab stop at the cursor.
+    TabSet,
+    ///DECALN, `ESC # 8`, which fills the screen with `E`s.
+    ScreenAlignmentTest,
     SetTopAndBottom(u32, u32),
+    SetIconNameAndWindowTitle(&'a str, Terminator),
+    SetIconName(&'a str, Terminator),
+    SetWindowTitle(&'a str, Terminator),
+    Hyperlink(Hyperlink<'a>, Terminator),
+    ///OSC 4, setting or querying entries of the 256 color palette. Also the terminal's reply
+    ///to a query.
+    PaletteColor(PaletteColors<'a>, Terminator),
+    ///OSC 104, resetting the given palette entries, or all of them if there are none.
+    ResetPaletteColor(PaletteIndices<'a>, Terminator),
+    ///OSC 10, 11 and 12, setting or querying the default colors or the cursor color. Each spec
+    ///after the first is for the next color, so `OSC 10 ; ? ; ?` queries both the foreground
+    ///and the background. Also the terminal's reply to a query.
+    DynamicColor(DynamicColor, Vec<ColorSpec<'a>, U3>, Terminator),
+    ///OSC 110, 111 and 112.
+    ResetDynamicColor(DynamicColor, Terminator),
+    ///OSC 52, setting or querying a selection. Also the terminal's reply to a query.
+    Clipboard(Clipboard<'a>, Terminator),
+    ///OSC 133, the FinalTerm semantic prompt marks.
+    SemanticPrompt(ShellMark<'a>, Terminator),
+    ///OSC 633.
+    VsCodeShellIntegration(VsCodeMark<'a>, Terminator),
+    ///OSC 7, the shell's working directory.
+    WorkingDirectory(FileUrl<'a>, Terminator),
+    ///OSC 1337 `CurrentDir=`, iTerm2's report of the shell's working directory.
+    CurrentDir(&'a str, Terminator),
+    ///Any other iTerm2 OSC 1337 command, such as `File`, `SetMark` or `SetUserVar`.
+    ITerm(ITermCommand<'a>, Terminator),
+    ///DECRQSS, `DCS $ q`, asking for the current value of a setting. The setting is named by
+    ///the final bytes of the sequence that changes it, such as `m` or ` q`.
+    RequestStatusString(&'a str, Terminator),
+    ///DECRPSS, `DCS Ps $ r`, the reply to DECRQSS. The flag tells if the request was valid, and
+    ///the string is the sequence that would restore the setting, without its `CSI`.
+    StatusStringReport(bool, &'a str, Terminator),
+    ///XTGETTCAP, `DCS + q`, asking for the value of terminfo capabilities.
+    RequestTermcap(Termcaps<'a>, Terminator),
+    ///`DCS Ps + r`, the reply to XTGETTCAP. The flag tells if the capabilities were known.
+    TermcapReport(bool, Termcaps<'a>, Terminator),
+    ///A kitty graphics protocol command, `APC G`.
+    KittyGraphics(KittyGraphics<'a>, Terminator),
+}
+
+///Which part of the display or line an erase clears, relative to the cursor.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EraseMode {
+    ///From the cursor to the end, which is what an erase without a parameter does.
+    ToEnd,
+    ///From the start up to and including the cursor.
+    ToStart,
+    All,
+    ///The scrollback buffer, which only the display has.
+    Scrollback,
+}
+
+impl Display for EraseMode {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        match self {
+            EraseMode::ToEnd => Ok(()),
+            EraseMode::ToStart => write!(formatter, "1"),
+            EraseMode::All => write!(formatter, "2"),
+            EraseMode::Scrollback => write!(formatter, "3"),
+        }
+    }
+}
+
+///A single parameter of a `SetGraphicsMode`, kept exactly as it was written so that it can be
+///emitted again byte for byte. `None` is a parameter that was left empty.
+///
+///A `SubParam` was separated from the parameter before it by a `:` rather than a `;`
+///(ITU T.416), and belongs to the closest preceding `Param`. For example `4:3` is
+///`[Param(Some(4)), SubParam(Some(3))]` and `38:2::1:2:3` starts with
+///`[Param(Some(38)), SubParam(Some(2)), SubParam(None), ...]`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SgrParam {
+    Param(Option<u8>),
+    SubParam(Option<u8>),
+}
+
+impl SgrParam {
+    ///The value of this parameter, with empty parameters read as 0.
+    pub fn value(self) -> u8 {
+        match self {
+            SgrParam::Param(val) | SgrParam::SubParam(val) => val.unwrap_or(0),
+        }
+    }
+}
+
+impl From<u8> for SgrParam {
+    fn from(val: u8) -> Self {
+        SgrParam::Param(Some(val))
+    }
+}
+
+///The target of an OSC 8 hyperlink. An empty `uri` closes the link that is currently open.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Hyperlink<'a> {
+    ///`:` separated `key=value` pairs.
+    pub params: &'a str,
+    pub uri: &'a str,
+}
+
+impl<'a> Hyperlink<'a> {
+    ///The `id` parameter, which ties together links that are split over several places.
+    pub fn id(&self) -> Option<&'a str> {
+        self.params
+            .split(':')
+            .find(|param| param.starts_with("id="))
+            .map(|param| &param["id=".len()..])
+    }
 }
 
 use core::fmt::{Display, Formatter, Result as DisplayResult};
-impl Display for AnsiSequence {
+
+fn write_modes(formatter: &mut Formatter, codes: impl Iterator<Item = u16>) -> DisplayResult {
+    for (i, code) in codes.enumerate() {
+        if i > 0 {
+            write!(formatter, ";")?;
+        }
+        write!(formatter, "{}", code)?;
+    }
+    Ok(())
+}
+
+impl<'a> Display for AnsiSequence<'a> {
     fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
         write!(formatter, "\u{1b}")?;
 
@@ -73,21 +285,48 @@ impl Display for AnsiSequence {
             CursorBackward(amt) => write!(formatter, "[{}D", amt),
             CursorSave => write!(formatter, "[s"),
             CursorRestore => write!(formatter, "[u"),
-            EraseDisplay => write!(formatter, "[2J"),
-            EraseDisplayFromCursor => write!(formatter, "[J"),
-            EraseLine => write!(formatter, "[K"),
-            SetGraphicsMode(vec) => match vec.len() {
-                0 => write!(formatter, "[m"),
-                1 => write!(formatter, "[{}m", vec[0]),
-                2 => write!(formatter, "[{};{}m", vec[0], vec[1]),
-                3 => write!(formatter, "[{};{};{}m", vec[0], vec[1], vec[2]),
-                5 => write!(
-                    formatter,
-                    "[{};{};{};{};{}m",
-                    vec[0], vec[1], vec[2], vec[3], vec[4]
-                ),
-                _ => unreachable!(),
-            },
+            CursorNextLine(amt) => write!(formatter, "[{}E", amt),
+            CursorPreviousLine(amt) => write!(formatter, "[{}F", amt),
+            CursorColumn(col) => write!(formatter, "[{}G", col),
+            CharacterPositionAbsolute(col) => write!(formatter, "[{}`", col),
+            LinePositionAbsolute(line) => write!(formatter, "[{}d", line),
+            CharacterPositionRelative(amt) => write!(formatter, "[{}a", amt),
+            LinePositionRelative(amt) => write!(formatter, "[{}e", amt),
+            CursorForwardTab(amt) => write!(formatter, "[{}I", amt),
+            CursorBackwardTab(amt) => write!(formatter, "[{}Z", amt),
+            EraseInDisplay(mode) => write!(formatter, "[{}J", mode),
+            EraseInLine(mode) => write!(formatter, "[{}K", mode),
+            SelectiveEraseInDisplay(mode) => write!(formatter, "[?{}J", mode),
+            SelectiveEraseInLine(mode) => write!(formatter, "[?{}K", mode),
+            EraseCharacters(amt) => write!(formatter, "[{}X", amt),
+            InsertCharacters(amt) => write!(formatter, "[{}@", amt),
+            DeleteCharacters(amt) => write!(formatter, "[{}P", amt),
+            InsertLines(amt) => write!(formatter, "[{}L", amt),
+            DeleteLines(amt) => write!(formatter, "[{}M", amt),
+            ScrollUp(amt) => write!(formatter, "[{}S", amt),
+            ScrollDown(amt) => write!(formatter, "[{}T", amt),
+            RepeatCharacter(amt) => write!(formatter, "[{}b", amt),
+            SetGraphicsMode(vec) => {
+                write!(formatter, "[")?;
+                for (i, param) in vec.iter().enumerate() {
+                    let val = match param {
+                        SgrParam::Param(val) => {
+                            if i > 0 {
+                                write!(formatter, ";")?;
+                            }
+                            val
+                        }
+                        SgrParam::SubParam(val) => {
+                            write!(formatter, ":")?;
+                            val
+                        }
+                    };
+                    if let Some(val) = val {
+                        write!(formatter, "{}", val)?;
+                    }
+                }
+                write!(formatter, "m")
+            }
             SetMode(mode) => write!(formatter, "[={}h", mode),
             ResetMode(mode) => write!(formatter, "[={}l", mode),
             ShowCursor => write!(formatter, "[?25h"),
@@ -111,6 +350,44 @@ impl Display for AnsiSequence {
             ResetAutoWrap => write!(formatter, "[?7l"),
             ResetAutoRepeat => write!(formatter, "[?8l"),
             ResetInterlacing => write!(formatter, "[?9l"),
+            DecPrivateMode(modes, set) => {
+                write!(formatter, "[?")?;
+                write_modes(formatter, modes.iter().map(|mode| mode.code()))?;
+                write!(formatter, "{}", if *set { "h" } else { "l" })
+            }
+            AnsiMode(modes, set) => {
+                write!(formatter, "[")?;
+                write_modes(formatter, modes.iter().map(|mode| mode.code()))?;
+                write!(formatter, "{}", if *set { "h" } else { "l" })
+            }
+            RequestDecMode(mode) => write!(formatter, "[?{}$p", mode.code()),
+            RequestAnsiMode(mode) => write!(formatter, "[{}$p", mode.code()),
+            DecModeReport(mode, state) => {
+                write!(formatter, "[?{};{}$y", mode.code(), state.code())
+            }
+            AnsiModeReport(mode, state) => {
+                write!(formatter, "[{};{}$y", mode.code(), state.code())
+            }
+            RequestPrimaryAttributes => write!(formatter, "[c"),
+            PrimaryAttributes(class, features) => {
+                write!(formatter, "[?{}", class)?;
+                for feature in features {
+                    write!(formatter, ";{}", feature)?;
+                }
+                write!(formatter, "c")
+            }
+            RequestSecondaryAttributes => write!(formatter, "[>c"),
+            SecondaryAttributes(kind, version, rom) => {
+                write!(formatter, "[>{};{};{}c", kind, version, rom)
+            }
+            RequestTertiaryAttributes => write!(formatter, "[=c"),
+            TertiaryAttributes(id, term) => write!(formatter, "P!|{}{}", id, term),
+            RequestDeviceStatus => write!(formatter, "[5n"),
+            DeviceStatusReport(ok) => write!(formatter, "[{}n", if *ok { 0 } else { 3 }),
+            RequestCursorPosition => write!(formatter, "[6n"),
+            CursorPositionReport(line, col) => write!(formatter, "[{};{}R", line, col),
+            RequestTerminalVersion => write!(formatter, "[>q"),
+            TerminalVersion(version, term) => write!(formatter, "P>|{}{}", version, term),
             SetAlternateKeypad => write!(formatter, "="),
             SetNumericKeypad => write!(formatter, ">"),
             SetUKG0 => write!(formatter, "(A"),
@@ -125,7 +402,49 @@ impl Display for AnsiSequence {
             SetG1AltAndSpecialGraph => write!(formatter, ")2"),
             SetSingleShift2 => write!(formatter, "N"),
             SetSingleShift3 => write!(formatter, "O"),
+            DecSaveCursor => write!(formatter, "7"),
+            DecRestoreCursor => write!(formatter, "8"),
+            FullReset => write!(formatter, "c"),
+            Index => write!(formatter, "D"),
+            NextLine => write!(formatter, "E"),
+            ReverseIndex => write!(formatter, "M"),
+            TabSet => write!(formatter, "H"),
+            ScreenAlignmentTest => write!(formatter, "#8"),
             SetTopAndBottom(x, y) => write!(formatter, "{};{}r", x, y),
+            SetIconNameAndWindowTitle(title, term) => write!(formatter, "]0;{}{}", title, term),
+            SetIconName(name, term) => write!(formatter, "]1;{}{}", name, term),
+            SetWindowTitle(title, term) => write!(formatter, "]2;{}{}", title, term),
+            Hyperlink(link, term) => write!(formatter, "]8;{};{}{}", link.params, link.uri, term),
+            PaletteColor(colors, term) => write!(formatter, "]4;{}{}", colors, term),
+            ResetPaletteColor(indices, term) if indices.is_empty() => {
+                write!(formatter, "]104{}", term)
+            }
+            ResetPaletteColor(indices, term) => write!(formatter, "]104;{}{}", indices, term),
+            DynamicColor(which, specs, term) => {
+                write!(formatter, "]{}", which.code())?;
+                for spec in specs {
+                    write!(formatter, ";{}", spec)?;
+                }
+                write!(formatter, "{}", term)
+            }
+            ResetDynamicColor(which, term) => write!(formatter, "]{}{}", which.code() + 100, term),
+            SemanticPrompt(mark, term) => write!(formatter, "]133;{}{}", mark, term),
+            VsCodeShellIntegration(mark, term) => write!(formatter, "]633;{}{}", mark, term),
+            WorkingDirectory(url, term) => write!(formatter, "]7;{}{}", url, term),
+            CurrentDir(dir, term) => write!(formatter, "]1337;CurrentDir={}{}", dir, term),
+            ITerm(cmd, term) => write!(formatter, "]1337;{}{}", cmd, term),
+            RequestStatusString(setting, term) => write!(formatter, "P$q{}{}", setting, term),
+            StatusStringReport(valid, setting, term) => {
+                write!(formatter, "P{}$r{}{}", *valid as u8, setting, term)
+            }
+            RequestTermcap(caps, term) => write!(formatter, "P+q{}{}", caps, term),
+            TermcapReport(valid, caps, term) => {
+                write!(formatter, "P{}+r{}{}", *valid as u8, caps, term)
+            }
+            KittyGraphics(cmd, term) => write!(formatter, "_{}{}", cmd, term),
+            Clipboard(clip, term) => {
+                write!(formatter, "]52;{};{}{}", clip.selection, clip.data, term)
+            }
         }
     }
 }
@@ -136,7 +455,9 @@ impl Display for AnsiSequence {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Output<'a> {
     TextBlock(&'a str),
-    Escape(AnsiSequence),
+    Escape(AnsiSequence<'a>),
+    ///A well-formed sequence that is not one of the `AnsiSequence`s above.
+    Unknown(Sequence<'a>),
 }
 
 impl<'a> Display for Output<'a> {
@@ -145,6 +466,122 @@ impl<'a> Display for Output<'a> {
         match self {
             TextBlock(txt) => write!(formatter, "{}", txt),
             Escape(seq) => write!(formatter, "{}", seq),
+            Unknown(seq) => write!(formatter, "{}", seq),
+        }
+    }
+}
+
+///This is what is outputted by the byte parsing iterator. Text is passed through as raw bytes,
+///so it may hold invalid UTF-8.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ByteOutput<'a> {
+    TextBlock(&'a [u8]),
+    Escape(AnsiSequence<'a>),
+    ///A well-formed sequence that is not one of the `AnsiSequence`s above.
+    Unknown(Sequence<'a>),
+}
+
+///How a control string (OSC, DCS, APC, PM or SOS) was terminated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Terminator {
+    ///`BEL`, `\x07`.
+    Bel,
+    ///String Terminator, `ESC \`.
+    St,
+}
+
+impl Display for Terminator {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        match self {
+            Terminator::Bel => write!(formatter, "\u{7}"),
+            Terminator::St => write!(formatter, "\u{1b}\\"),
+        }
+    }
+}
+
+///A Control Sequence: `ESC [`, followed by parameters, intermediates and a final byte.
+///Everything is kept as it was written, so the parameters include any separators.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Csi<'a> {
+    pub private_marker: Option<char>,
+    pub params: &'a str,
+    pub intermediates: &'a str,
+    pub final_byte: char,
+}
+
+///An escape sequence that isn't introducing a control sequence or string, such as `ESC 7` or
+///`ESC ( B`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Esc<'a> {
+    pub intermediates: &'a str,
+    pub final_byte: char,
+}
+
+///The payload of an OSC, APC, PM or SOS string.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ControlString<'a> {
+    pub data: &'a str,
+    pub terminator: Terminator,
+}
+
+///A Device Control String. The header is shaped like a control sequence, and is followed by
+///the data string.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Dcs<'a> {
+    pub private_marker: Option<char>,
+    pub params: &'a str,
+    pub intermediates: &'a str,
+    pub final_byte: char,
+    pub data: &'a str,
+    pub terminator: Terminator,
+}
+
+///Any well-formed ECMA-48 escape sequence, split into its syntactic parts but otherwise
+///uninterpreted. `AnsiSequence` gives meaning to the ones this library knows about.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Sequence<'a> {
+    Csi(Csi<'a>),
+    Esc(Esc<'a>),
+    Osc(ControlString<'a>),
+    Dcs(Dcs<'a>),
+    Apc(ControlString<'a>),
+    Pm(ControlString<'a>),
+    Sos(ControlString<'a>),
+}
+
+impl<'a> Display for Sequence<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "\u{1b}")?;
+
+        use Sequence::*;
+        match self {
+            Csi(csi) => {
+                write!(formatter, "[")?;
+                if let Some(marker) = csi.private_marker {
+                    write!(formatter, "{}", marker)?;
+                }
+                write!(
+                    formatter,
+                    "{}{}{}",
+                    csi.params, csi.intermediates, csi.final_byte
+                )
+            }
+            Esc(esc) => write!(formatter, "{}{}", esc.intermediates, esc.final_byte),
+            Osc(osc) => write!(formatter, "]{}{}", osc.data, osc.terminator),
+            Dcs(dcs) => {
+                write!(formatter, "P")?;
+                if let Some(marker) = dcs.private_marker {
+                    write!(formatter, "{}", marker)?;
+                }
+                write!(
+                    formatter,
+                    "{}{}{}{}{}",
+                    dcs.params, dcs.intermediates, dcs.final_byte, dcs.data, dcs.terminator
+                )
+            }
+            Apc(apc) => write!(formatter, "_{}{}", apc.data, apc.terminator),
+            Pm(pm) => write!(formatter, "^{}{}", pm.data, pm.terminator),
+            Sos(sos) => write!(formatter, "X{}{}", sos.data, sos.terminator),
         }
     }
 }
@@ -0,0 +1,548 @@
+#[cfg(test)]
+mod tests;
+
+use crate::mouse::{self, MouseEvent};
+use crate::parsers::utf8_width;
+
+use core::str;
+use nom::{Err, IResult, Needed};
+
+///The modifier keys held down along with a key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        shift: false,
+        alt: false,
+        ctrl: false,
+        meta: false,
+    };
+
+    pub const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+
+    pub const ALT: Modifiers = Modifiers {
+        alt: true,
+        ..Modifiers::NONE
+    };
+
+    pub const CTRL: Modifiers = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+
+    ///Reads the modifier parameter that xterm adds to keys, such as the 5 in `CSI 1 ; 5 A`,
+    ///which is one more than the modifiers as bits.
+    pub fn from_param(param: u16) -> Option<Modifiers> {
+        let bits = param.checked_sub(1).filter(|&bits| bits < 16)?;
+        Some(Modifiers {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+            meta: bits & 8 != 0,
+        })
+    }
+
+    ///The modifier parameter that xterm would send for these modifiers.
+    pub fn param(self) -> u16 {
+        1 + self.shift as u16
+            + ((self.alt as u16) << 1)
+            + ((self.ctrl as u16) << 2)
+            + ((self.meta as u16) << 3)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self == Modifiers::NONE
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum KeyCode {
+    Char(char),
+    Enter,
+    Tab,
+    ///Shift and tab, which terminals send as `CSI Z`.
+    BackTab,
+    Backspace,
+    Escape,
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    ///The middle of the keypad, with num lock off.
+    Begin,
+    ///A function key. Up to F20 have codes of their own, as do F21 to F44 in rxvt, while xterm
+    ///sends F13 to F24 as F1 to F12 with `shift`, and they are read as such.
+    F(u8),
+}
+
+///A key press, as a terminal sends it.
+///
+///Control characters come through as the key they are typed with, so `0x01` is `a` with
+///`ctrl`. Upper case letters are not marked with `shift`, as the terminal has already applied
+///it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: Modifiers) -> Key {
+        Key { code, modifiers }
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Key {
+        Key::new(code, Modifiers::NONE)
+    }
+}
+
+///Something the terminal sent as input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InputEvent<'a> {
+    Key(Key),
+    Mouse(MouseEvent),
+    ///Text pasted while bracketed paste (mode 2004) is on, without the brackets.
+    Paste(&'a str),
+    ///The terminal window gained focus, reported while mode 1004 is on.
+    FocusIn,
+    ///The terminal window lost focus.
+    FocusOut,
+    ///A well-formed sequence that isn't known, or bytes that aren't valid UTF-8.
+    Unknown(&'a [u8]),
+}
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+//Everything after the start of a paste, up to and including its end. Pasted text that isn't
+//valid UTF-8 is left unknown.
+fn paste(input: &[u8]) -> IResult<&[u8], InputEvent<'_>> {
+    let len = input
+        .windows(PASTE_END.len())
+        .position(|window| window == PASTE_END)
+        .ok_or(Err::Incomplete(Needed::Unknown))?;
+    let event = match str::from_utf8(&input[..len]) {
+        Ok(text) => InputEvent::Paste(text),
+        Err(_) => InputEvent::Unknown(&input[..len]),
+    };
+    Ok((&input[len + PASTE_END.len()..], event))
+}
+
+fn key<'a>(code: KeyCode, modifiers: Modifiers) -> InputEvent<'a> {
+    InputEvent::Key(Key::new(code, modifiers))
+}
+
+fn control(byte: u8) -> InputEvent<'static> {
+    match byte {
+        b'\r' => key(KeyCode::Enter, Modifiers::NONE),
+        b'\t' => key(KeyCode::Tab, Modifiers::NONE),
+        0x7f => key(KeyCode::Backspace, Modifiers::NONE),
+        0 => key(KeyCode::Char(' '), Modifiers::CTRL),
+        0x01..=0x1a => key(KeyCode::Char((byte - 1 + b'a') as char), Modifiers::CTRL),
+        _ => key(KeyCode::Char((byte + 0x40) as char), Modifiers::CTRL),
+    }
+}
+
+//The keys that end in a letter, after `CSI` or `SS3`.
+fn letter_key(final_byte: u8) -> Option<KeyCode> {
+    Some(match final_byte {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'E' => KeyCode::Begin,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    })
+}
+
+//The keys that end in `~`, by their number. The function keys skip a number here and there,
+//as they did on the VT220.
+fn tilde_key(num: u16) -> Option<KeyCode> {
+    Some(match num {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        11..=15 => KeyCode::F((num - 10) as u8),
+        17..=21 => KeyCode::F((num - 11) as u8),
+        23..=26 => KeyCode::F((num - 12) as u8),
+        28 | 29 => KeyCode::F((num - 13) as u8),
+        31..=34 => KeyCode::F((num - 14) as u8),
+        _ => return None,
+    })
+}
+
+//rxvt ends keys in `$` for shift, `^` for ctrl and `@` for both, rather than adding a modifier
+//parameter. Its function keys are numbered on past F20 instead: F21 and F22 are F11 and F12
+//ending in `$`, F23 to F42 are F1 to F20 ending in `^`, and F43 and F44 are F11 and F12 ending
+//in `@`.
+fn rxvt_key(params: &[u8], final_byte: u8) -> Option<Key> {
+    let num = str::from_utf8(params).ok()?.parse().ok()?;
+    let modifiers = match final_byte {
+        b'$' => Modifiers::SHIFT,
+        b'^' => Modifiers::CTRL,
+        _ => Modifiers {
+            shift: true,
+            ctrl: true,
+            ..Modifiers::NONE
+        },
+    };
+    let code = match (final_byte, tilde_key(num)?) {
+        (b'$', KeyCode::F(num @ 11..=12)) => KeyCode::F(num + 10),
+        (b'^', KeyCode::F(num)) => KeyCode::F(num + 22),
+        (b'@', KeyCode::F(num @ 11..=12)) => KeyCode::F(num + 32),
+        (_, KeyCode::F(_)) => return None,
+        (_, code) if (2..=8).contains(&num) => return Some(Key::new(code, modifiers)),
+        _ => return None,
+    };
+    Some(code.into())
+}
+
+//Up to two `;` separated numbers, where an empty one counts as 1.
+fn key_params(params: &[u8]) -> Option<(u16, Modifiers)> {
+    let params = str::from_utf8(params).ok()?;
+    let number = |param: &str| -> Option<u16> {
+        if param.is_empty() {
+            Some(1)
+        } else {
+            param.parse().ok()
+        }
+    };
+    match params.split_once(';') {
+        Some((num, mods)) => Some((number(num)?, Modifiers::from_param(number(mods)?)?)),
+        None => Some((number(params)?, Modifiers::NONE)),
+    }
+}
+
+fn csi_key(params: &[u8], final_byte: u8) -> Option<InputEvent<'static>> {
+    let (num, modifiers) = key_params(params)?;
+    let code = match final_byte {
+        b'~' => tilde_key(num)?,
+        b'Z' if params.is_empty() => KeyCode::BackTab,
+        _ if num == 1 => letter_key(final_byte)?,
+        _ => return None,
+    };
+    Some(key(code, modifiers))
+}
+
+//Everything after `ESC [`.
+fn csi(input: &[u8]) -> IResult<&[u8], InputEvent<'_>> {
+    //An X10 mouse report, which has three bytes of its own after the `M`.
+    if input.first() == Some(&b'M') {
+        let data = input
+            .get(1..4)
+            .ok_or_else(|| Err::Incomplete(Needed::Size(4 - input.len())))?;
+        let event = match mouse::parse_x10(data) {
+            Some(event) => InputEvent::Mouse(event),
+            None => InputEvent::Unknown(&input[..4]),
+        };
+        return Ok((&input[4..], event));
+    }
+
+    //The Linux console sends F1 to F5 as `CSI [ A` to `CSI [ E`.
+    if input.first() == Some(&b'[') {
+        return match input.get(1) {
+            Some(&letter @ b'A'..=b'E') => {
+                let code = KeyCode::F(letter - b'A' + 1);
+                Ok((&input[2..], key(code, Modifiers::NONE)))
+            }
+            Some(_) => Ok((&input[1..], InputEvent::Unknown(b"\x1b[["))),
+            None => Err(Err::Incomplete(Needed::Size(1))),
+        };
+    }
+
+    let params = input
+        .iter()
+        .position(|b| !(0x30..=0x3f).contains(b))
+        .ok_or(Err::Incomplete(Needed::Size(1)))?;
+    //rxvt ends keys with `$` after a number, which would otherwise be read as an intermediate
+    //byte and take the next key press along as the final one.
+    let digits = &input[..params];
+    if input[params] == b'$' && !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) {
+        let event = rxvt_key(digits, b'$')
+            .map(InputEvent::Key)
+            .unwrap_or(InputEvent::Unknown(&input[..params + 1]));
+        return Ok((&input[params + 1..], event));
+    }
+    let intermediates = input[params..]
+        .iter()
+        .position(|b| !(0x20..=0x2f).contains(b))
+        .ok_or(Err::Incomplete(Needed::Size(1)))?;
+    let end = params + intermediates;
+    let final_byte = input[end];
+    //Cut short by something that can't end it, which is left alone.
+    if !(0x40..=0x7e).contains(&final_byte) {
+        return Ok((&input[end..], InputEvent::Unknown(&input[..end])));
+    }
+
+    let params = &input[..params];
+    if params == b"200" && final_byte == b'~' {
+        return paste(&input[end + 1..]);
+    }
+
+    let event = match (intermediates, final_byte) {
+        (0, b'I') if params.is_empty() => Some(InputEvent::FocusIn),
+        (0, b'O') if params.is_empty() => Some(InputEvent::FocusOut),
+        (0, b'M') | (0, b'm') => mouse::parse_params(params, final_byte).map(InputEvent::Mouse),
+        (0, b'^') | (0, b'@') => rxvt_key(params, final_byte).map(InputEvent::Key),
+        (0, _) => csi_key(params, final_byte),
+        _ => None,
+    };
+    Ok((
+        &input[end + 1..],
+        event.unwrap_or(InputEvent::Unknown(&input[..end + 1])),
+    ))
+}
+
+//Everything after `ESC O`, which may have a modifier parameter before its final byte.
+fn ss3(input: &[u8]) -> IResult<&[u8], InputEvent<'_>> {
+    let digits = input
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .ok_or(Err::Incomplete(Needed::Size(1)))?;
+    let modifiers = match digits {
+        0 => Some(Modifiers::NONE),
+        _ => str::from_utf8(&input[..digits])
+            .ok()
+            .and_then(|param| param.parse().ok())
+            .and_then(Modifiers::from_param),
+    };
+    //Cut short by something that can't end it, which is left alone.
+    if !(0x40..=0x7e).contains(&input[digits]) {
+        return Ok((&input[digits..], InputEvent::Unknown(&input[..digits])));
+    }
+    //The keypad's enter key, in application mode.
+    let code = match input[digits] {
+        b'M' => Some(KeyCode::Enter),
+        final_byte => letter_key(final_byte),
+    };
+
+    let rest = &input[digits + 1..];
+    match (code, modifiers) {
+        (Some(code), Some(modifiers)) => Ok((rest, key(code, modifiers))),
+        _ => Ok((rest, InputEvent::Unknown(&input[..digits + 1]))),
+    }
+}
+
+//Adds the offset of the sequence's introducer back into an unknown sequence.
+fn with_prefix<'a>(
+    input: &'a [u8],
+    result: IResult<&'a [u8], InputEvent<'a>>,
+) -> IResult<&'a [u8], InputEvent<'a>> {
+    let (rest, event) = result?;
+    let event = match event {
+        InputEvent::Unknown(_) => InputEvent::Unknown(&input[..input.len() - rest.len()]),
+        event => event,
+    };
+    Ok((rest, event))
+}
+
+///Parses a single event off the start of terminal input, such as a key press, a mouse report
+///or a paste.
+///
+///Runs out of input on a lone `ESC`, as it could be the start of a sequence. If no more input
+///follows, it was the escape key. `AnsiInputParser` does this for input that is all there.
+///A paste also runs out of input until its end has arrived, which `InputStreamParser` waits
+///for.
+pub fn parse_input(input: &[u8]) -> IResult<&[u8], InputEvent<'_>> {
+    let first = *input.first().ok_or(Err::Incomplete(Needed::Size(1)))?;
+    match first {
+        0x1b => match *input.get(1).ok_or(Err::Incomplete(Needed::Size(1)))? {
+            b'[' => with_prefix(input, csi(&input[2..])),
+            b'O' => with_prefix(input, ss3(&input[2..])),
+            //Alt and a key is sent as `ESC` followed by the key. Only the one key after it is
+            //looked at, so a run of `ESC`s is read one at a time instead of nesting.
+            _ => {
+                let (rest, event) = match (input[1], input.get(2)) {
+                    (0x1b, None) => return Err(Err::Incomplete(Needed::Size(1))),
+                    (0x1b, Some(b'[')) => csi(&input[3..])?,
+                    (0x1b, Some(b'O')) => ss3(&input[3..])?,
+                    (0x1b, Some(_)) => {
+                        return Ok((&input[1..], key(KeyCode::Escape, Modifiers::NONE)))
+                    }
+                    _ => parse_input(&input[1..])?,
+                };
+                match event {
+                    InputEvent::Key(mut key) if !key.modifiers.alt => {
+                        key.modifiers.alt = true;
+                        Ok((rest, InputEvent::Key(key)))
+                    }
+                    _ => Ok((&input[1..], key(KeyCode::Escape, Modifiers::NONE))),
+                }
+            }
+        },
+        0..=0x1f | 0x7f => Ok((&input[1..], control(first))),
+        _ => {
+            let width = utf8_width(first);
+            //Only a character that could still be finished runs out of input. One that is
+            //broken off by something else is unknown, up to where it was broken off.
+            let cont = input[1..]
+                .iter()
+                .take(width - 1)
+                .take_while(|byte| (0x80..=0xbf).contains(*byte))
+                .count();
+            if cont < width - 1 && 1 + cont < input.len() {
+                return Ok((&input[1 + cont..], InputEvent::Unknown(&input[..1 + cont])));
+            }
+            let bytes = input
+                .get(..width)
+                .ok_or_else(|| Err::Incomplete(Needed::Size(width - input.len())))?;
+            match str::from_utf8(bytes).ok().and_then(|c| c.chars().next()) {
+                Some(c) => Ok((&input[width..], key(KeyCode::Char(c), Modifiers::NONE))),
+                None => Ok((&input[1..], InputEvent::Unknown(&input[..1]))),
+            }
+        }
+    }
+}
+
+pub trait AnsiInputParser {
+    fn input_parse(&self) -> InputParseIterator<'_>;
+}
+
+impl AnsiInputParser for [u8] {
+    fn input_parse(&self) -> InputParseIterator<'_> {
+        InputParseIterator { dat: self }
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+impl AnsiInputParser for std::vec::Vec<u8> {
+    fn input_parse(&self) -> InputParseIterator<'_> {
+        InputParseIterator { dat: self }
+    }
+}
+
+///Parses input that has been read in full, such as everything one read from a terminal
+///returned. An `ESC` that nothing complete follows is taken to be the escape key, or alt and
+///the escape key when there are two of them, and a paste that doesn't end runs to the end of
+///the input.
+#[derive(Debug)]
+pub struct InputParseIterator<'a> {
+    dat: &'a [u8],
+}
+
+impl<'a> Iterator for InputParseIterator<'a> {
+    type Item = InputEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.dat.is_empty() {
+            return None;
+        }
+
+        match parse_input(self.dat) {
+            Ok((rest, event)) => {
+                self.dat = rest;
+                Some(event)
+            }
+            Err(_) if self.dat.starts_with(PASTE_START) => {
+                let text = &self.dat[PASTE_START.len()..];
+                self.dat = &[];
+                match str::from_utf8(text) {
+                    Ok(text) => Some(InputEvent::Paste(text)),
+                    Err(_) => Some(InputEvent::Unknown(text)),
+                }
+            }
+            Err(_) if self.dat == b"\x1b\x1b" => {
+                self.dat = &[];
+                Some(key(KeyCode::Escape, Modifiers::ALT))
+            }
+            Err(_) if self.dat[0] == 0x1b => {
+                self.dat = &self.dat[1..];
+                Some(key(KeyCode::Escape, Modifiers::NONE))
+            }
+            //A character that was cut off.
+            Err(_) => {
+                let event = InputEvent::Unknown(self.dat);
+                self.dat = &[];
+                Some(event)
+            }
+        }
+    }
+}
+
+///Parses input that arrives in pieces, such as reads from a terminal. Events that are cut off
+///at the end of one piece, including pastes that take several reads, are held on to until
+///they are finished.
+#[cfg(any(feature = "std", test))]
+#[derive(Debug, Default)]
+pub struct InputStreamParser {
+    buf: std::vec::Vec<u8>,
+    //How much of `buf` was returned as events.
+    used: usize,
+}
+
+#[cfg(any(feature = "std", test))]
+impl InputStreamParser {
+    pub fn new() -> InputStreamParser {
+        InputStreamParser::default()
+    }
+
+    ///Whether part of an event is being held on to.
+    pub fn is_pending(&self) -> bool {
+        self.used < self.buf.len()
+    }
+
+    ///Parses the next piece of input. Complete events are returned right away, the rest is
+    ///kept until the next call to `feed` or `finish`.
+    pub fn feed(&mut self, chunk: &[u8]) -> InputStreamIterator<'_> {
+        self.buf.drain(..self.used);
+        self.buf.extend_from_slice(chunk);
+        self.used = 0;
+        InputStreamIterator {
+            dat: &self.buf,
+            used: &mut self.used,
+        }
+    }
+
+    ///Ends the input, parsing whatever was held back as `AnsiInputParser` does. A lone `ESC`
+    ///is the escape key, which is worth calling this for once reads have gone quiet.
+    pub fn finish(&mut self) -> InputParseIterator<'_> {
+        self.buf.drain(..self.used);
+        self.used = self.buf.len();
+        self.buf.input_parse()
+    }
+}
+
+///The complete events in the input fed to an `InputStreamParser`.
+#[cfg(any(feature = "std", test))]
+#[derive(Debug)]
+pub struct InputStreamIterator<'a> {
+    dat: &'a [u8],
+    used: &'a mut usize,
+}
+
+#[cfg(any(feature = "std", test))]
+impl<'a> Iterator for InputStreamIterator<'a> {
+    type Item = InputEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (rest, event) = parse_input(&self.dat[*self.used..]).ok()?;
+        *self.used = self.dat.len() - rest.len();
+        Some(event)
+    }
+}
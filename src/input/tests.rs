@@ -0,0 +1,354 @@
+use crate::*;
+
+use nom::Err;
+use std::vec::Vec;
+
+fn keys(input: &[u8]) -> Vec<InputEvent<'_>> {
+    input.input_parse().collect()
+}
+
+fn plain(code: KeyCode) -> InputEvent<'static> {
+    InputEvent::Key(code.into())
+}
+
+fn with(code: KeyCode, modifiers: Modifiers) -> InputEvent<'static> {
+    InputEvent::Key(Key::new(code, modifiers))
+}
+
+#[test]
+fn test_modifiers() {
+    for param in 1..=16 {
+        assert_eq!(Modifiers::from_param(param).unwrap().param(), param);
+    }
+    assert_eq!(Modifiers::from_param(0), None);
+    assert_eq!(Modifiers::from_param(17), None);
+    assert_eq!(
+        Modifiers::from_param(6),
+        Some(Modifiers {
+            shift: true,
+            ctrl: true,
+            ..Modifiers::NONE
+        })
+    );
+    assert!(Modifiers::from_param(1).unwrap().is_empty());
+}
+
+#[test]
+fn test_text() {
+    assert_eq!(
+        keys("aé😀 ".as_bytes()),
+        [
+            plain(KeyCode::Char('a')),
+            plain(KeyCode::Char('é')),
+            plain(KeyCode::Char('😀')),
+            plain(KeyCode::Char(' ')),
+        ]
+    );
+    assert_eq!(
+        keys(b"\r\t\x7f\x01\x00\x1f"),
+        [
+            plain(KeyCode::Enter),
+            plain(KeyCode::Tab),
+            plain(KeyCode::Backspace),
+            with(KeyCode::Char('a'), Modifiers::CTRL),
+            with(KeyCode::Char(' '), Modifiers::CTRL),
+            with(KeyCode::Char('_'), Modifiers::CTRL),
+        ]
+    );
+    assert_eq!(
+        keys(b"\xffa\xe2\x82"),
+        [
+            InputEvent::Unknown(b"\xff"),
+            plain(KeyCode::Char('a')),
+            InputEvent::Unknown(b"\xe2\x82"),
+        ]
+    );
+    //A character broken off by another doesn't take it along.
+    assert_eq!(
+        keys(b"\xe2a\xf0\x9f\xe2\x82\xac"),
+        [
+            InputEvent::Unknown(b"\xe2"),
+            plain(KeyCode::Char('a')),
+            InputEvent::Unknown(b"\xf0\x9f"),
+            plain(KeyCode::Char('€')),
+        ]
+    );
+}
+
+#[test]
+fn test_cursor_keys() {
+    assert_eq!(
+        keys(b"\x1b[A\x1bOB\x1b[1;5C\x1b[1;3D\x1b[H\x1bOF\x1b[E\x1b[Z"),
+        [
+            plain(KeyCode::Up),
+            plain(KeyCode::Down),
+            with(KeyCode::Right, Modifiers::CTRL),
+            with(KeyCode::Left, Modifiers::ALT),
+            plain(KeyCode::Home),
+            plain(KeyCode::End),
+            plain(KeyCode::Begin),
+            plain(KeyCode::BackTab),
+        ]
+    );
+    assert_eq!(
+        keys(b"\x1bO5A\x1bOM"),
+        [with(KeyCode::Up, Modifiers::CTRL), plain(KeyCode::Enter),]
+    );
+}
+
+#[test]
+fn test_editing_keys() {
+    assert_eq!(
+        keys(b"\x1b[1~\x1b[2~\x1b[3;2~\x1b[4~\x1b[5~\x1b[6;5~\x1b[7~\x1b[8~"),
+        [
+            plain(KeyCode::Home),
+            plain(KeyCode::Insert),
+            with(KeyCode::Delete, Modifiers::SHIFT),
+            plain(KeyCode::End),
+            plain(KeyCode::PageUp),
+            with(KeyCode::PageDown, Modifiers::CTRL),
+            plain(KeyCode::Home),
+            plain(KeyCode::End),
+        ]
+    );
+}
+
+#[test]
+fn test_function_keys() {
+    let mut input = Vec::new();
+    for num in &[
+        11, 12, 13, 14, 15, 17, 18, 19, 20, 21, 23, 24, 25, 26, 28, 29, 31, 32, 33, 34,
+    ] {
+        input.extend_from_slice(format!("\x1b[{}~", num).as_bytes());
+    }
+    let expected: Vec<_> = (1..=20).map(|num| plain(KeyCode::F(num))).collect();
+    assert_eq!(keys(&input), expected);
+
+    assert_eq!(
+        keys(b"\x1bOP\x1bOS\x1b[1;2P\x1b[24;2~\x1b[[A\x1b[[E"),
+        [
+            plain(KeyCode::F(1)),
+            plain(KeyCode::F(4)),
+            with(KeyCode::F(1), Modifiers::SHIFT),
+            with(KeyCode::F(12), Modifiers::SHIFT),
+            plain(KeyCode::F(1)),
+            plain(KeyCode::F(5)),
+        ]
+    );
+
+    //rxvt's own numbering past F20.
+    assert_eq!(
+        keys(b"\x1b[23$\x1b[24$\x1b[11^\x1b[12^\x1b[34^\x1b[24@a"),
+        [
+            plain(KeyCode::F(21)),
+            plain(KeyCode::F(22)),
+            plain(KeyCode::F(23)),
+            plain(KeyCode::F(24)),
+            plain(KeyCode::F(42)),
+            plain(KeyCode::F(44)),
+            plain(KeyCode::Char('a')),
+        ]
+    );
+    assert_eq!(
+        keys(b"\x1b[11@\x1b[25$x"),
+        [
+            InputEvent::Unknown(b"\x1b[11@"),
+            InputEvent::Unknown(b"\x1b[25$"),
+            plain(KeyCode::Char('x')),
+        ]
+    );
+}
+
+#[test]
+fn test_rxvt_editing_keys() {
+    let ctrl_shift = Modifiers {
+        shift: true,
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+    assert_eq!(
+        keys(b"\x1b[2$\x1b[3$x\x1b[5$\x1b[6$\x1b[7$\x1b[8$\x1b[5^\x1b[3@"),
+        [
+            with(KeyCode::Insert, Modifiers::SHIFT),
+            with(KeyCode::Delete, Modifiers::SHIFT),
+            plain(KeyCode::Char('x')),
+            with(KeyCode::PageUp, Modifiers::SHIFT),
+            with(KeyCode::PageDown, Modifiers::SHIFT),
+            with(KeyCode::Home, Modifiers::SHIFT),
+            with(KeyCode::End, Modifiers::SHIFT),
+            with(KeyCode::PageUp, Modifiers::CTRL),
+            with(KeyCode::Delete, ctrl_shift),
+        ]
+    );
+    //Nothing has to follow the `$`.
+    assert_eq!(
+        parse_input(b"\x1b[3$"),
+        Ok((&b""[..], with(KeyCode::Delete, Modifiers::SHIFT)))
+    );
+}
+
+#[test]
+fn test_alt() {
+    assert_eq!(
+        keys(b"\x1ba\x1b\x01\x1b\x1b[A\x1b\x1b"),
+        [
+            with(KeyCode::Char('a'), Modifiers::ALT),
+            with(
+                KeyCode::Char('a'),
+                Modifiers {
+                    alt: true,
+                    ctrl: true,
+                    ..Modifiers::NONE
+                }
+            ),
+            with(KeyCode::Up, Modifiers::ALT),
+            with(KeyCode::Escape, Modifiers::ALT),
+        ]
+    );
+    //A key that already has alt isn't given it twice.
+    assert_eq!(
+        keys(b"\x1b\x1b[1;3A"),
+        [plain(KeyCode::Escape), with(KeyCode::Up, Modifiers::ALT)]
+    );
+}
+
+#[test]
+fn test_cut_off_ss3() {
+    assert_eq!(
+        keys(b"\x1bO\x1b[A\x1bO1\x7f"),
+        [
+            InputEvent::Unknown(b"\x1bO"),
+            plain(KeyCode::Up),
+            InputEvent::Unknown(b"\x1bO1"),
+            plain(KeyCode::Backspace),
+        ]
+    );
+}
+
+#[test]
+fn test_escape_run() {
+    //Read one `ESC` at a time, rather than nesting a level for each.
+    let input = vec![0x1b; 100_000];
+    let parsed = keys(&input);
+    assert_eq!(parsed.len(), input.len() - 1);
+    assert_eq!(parsed[0], plain(KeyCode::Escape));
+    assert_eq!(
+        parsed[parsed.len() - 1],
+        with(KeyCode::Escape, Modifiers::ALT)
+    );
+
+    let mut input = vec![0x1b; 100_000];
+    input.extend_from_slice(b"[A");
+    let parsed = keys(&input);
+    assert_eq!(parsed.len(), input.len() - 3);
+    assert_eq!(parsed[parsed.len() - 1], with(KeyCode::Up, Modifiers::ALT));
+}
+
+#[test]
+fn test_unknown() {
+    assert_eq!(
+        keys(b"\x1b[99~\x1b[5n\x1b[1 A\x1bOz\x1b[1\rx"),
+        [
+            InputEvent::Unknown(b"\x1b[99~"),
+            InputEvent::Unknown(b"\x1b[5n"),
+            InputEvent::Unknown(b"\x1b[1 A"),
+            InputEvent::Unknown(b"\x1bOz"),
+            InputEvent::Unknown(b"\x1b[1"),
+            plain(KeyCode::Enter),
+            plain(KeyCode::Char('x')),
+        ]
+    );
+}
+
+#[test]
+fn test_incomplete() {
+    for input in &[
+        &b"\x1b"[..],
+        b"\x1b[",
+        b"\x1b[1;5",
+        b"\x1bO",
+        b"\x1b[[",
+        b"\xf0\x9f",
+    ] {
+        assert!(matches!(parse_input(input), Err(Err::Incomplete(_))));
+    }
+    assert_eq!(parse_input(b"\x1b[Ab"), Ok((&b"b"[..], plain(KeyCode::Up))));
+}
+
+#[test]
+fn test_focus() {
+    assert_eq!(
+        keys(b"\x1b[I\x1b[O\x1b[2I\x1bOI"),
+        [
+            InputEvent::FocusIn,
+            InputEvent::FocusOut,
+            InputEvent::Unknown(b"\x1b[2I"),
+            InputEvent::Unknown(b"\x1bOI"),
+        ]
+    );
+}
+
+#[test]
+fn test_paste() {
+    assert_eq!(
+        keys(b"a\x1b[200~ls -l\x1b[A\r\n\x1b[201~b\x1b[200~\x1b[201~"),
+        [
+            plain(KeyCode::Char('a')),
+            InputEvent::Paste("ls -l\x1b[A\r\n"),
+            plain(KeyCode::Char('b')),
+            InputEvent::Paste(""),
+        ]
+    );
+    assert_eq!(
+        keys(b"\x1b[200~\xff\x1b[201~\x1b[201~"),
+        [
+            InputEvent::Unknown(b"\x1b[200~\xff\x1b[201~"),
+            InputEvent::Unknown(b"\x1b[201~"),
+        ]
+    );
+
+    //A paste that never ends takes up the rest of the input.
+    assert!(matches!(
+        parse_input(b"\x1b[200~abc\x1b[201"),
+        Err(Err::Incomplete(_))
+    ));
+    assert_eq!(
+        keys(b"x\x1b[200~abc\x1b[201"),
+        [plain(KeyCode::Char('x')), InputEvent::Paste("abc\x1b[201")]
+    );
+}
+
+#[test]
+fn test_stream() {
+    let mut parser = InputStreamParser::new();
+    assert_eq!(
+        parser.feed(b"q\x1b[1;").collect::<Vec<_>>(),
+        [plain(KeyCode::Char('q'))]
+    );
+    assert!(parser.is_pending());
+    assert_eq!(
+        parser.feed(b"5A\x1b[200~first ").collect::<Vec<_>>(),
+        [with(KeyCode::Up, Modifiers::CTRL)]
+    );
+    assert_eq!(parser.feed("lïne\x1b[2".as_bytes()).count(), 0);
+    assert_eq!(
+        parser.feed(b"01~\x1b[I").collect::<Vec<_>>(),
+        [InputEvent::Paste("first lïne"), InputEvent::FocusIn]
+    );
+    assert!(!parser.is_pending());
+
+    //A lone escape could still be the start of something, until the input ends.
+    assert_eq!(parser.feed(b"\x1b").count(), 0);
+    assert_eq!(
+        parser.finish().collect::<Vec<_>>(),
+        [plain(KeyCode::Escape)]
+    );
+    assert_eq!(parser.finish().count(), 0);
+
+    //So could the first byte of a character.
+    assert_eq!(parser.feed(b"\xc3").count(), 0);
+    assert_eq!(
+        parser.feed(b"\xa9").collect::<Vec<_>>(),
+        [plain(KeyCode::Char('é'))]
+    );
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests;
+
+use crate::base64;
+
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+
+///An iTerm2 OSC 1337 command, such as `SetMark` or `SetUserVar=name=value`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ITermCommand<'a> {
+    pub key: &'a str,
+    ///Everything after the first `=`, if there is one.
+    pub value: Option<&'a str>,
+}
+
+impl<'a> ITermCommand<'a> {
+    ///Reads the data of an OSC 1337 string, leaving out the `1337;`.
+    pub fn parse(data: &'a str) -> Option<ITermCommand<'a>> {
+        let mut parts = data.splitn(2, '=');
+        let key = parts.next().unwrap_or("");
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return None;
+        }
+        Some(ITermCommand {
+            key,
+            value: parts.next(),
+        })
+    }
+
+    ///The file sent by a `File` command, which is either shown inline or downloaded.
+    pub fn file(&self) -> Option<ITermFile<'a>> {
+        match (self.key, self.value) {
+            ("File", Some(value)) => ITermFile::parse(value),
+            _ => None,
+        }
+    }
+
+    ///The name of the variable set by `SetUserVar`, along with its base64 encoded value.
+    pub fn user_var(&self) -> Option<(&'a str, &'a str)> {
+        match (self.key, self.value) {
+            ("SetUserVar", Some(value)) => value.split_once('='),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Display for ITermCommand<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{}", self.key)?;
+        match self.value {
+            Some(value) => write!(formatter, "={}", value),
+            None => Ok(()),
+        }
+    }
+}
+
+///The arguments and contents of an iTerm2 `File` command.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ITermFile<'a> {
+    ///The `;` separated `key=value` arguments, such as `name`, `size` and `inline`.
+    pub args: &'a str,
+    ///The base64 encoded contents.
+    pub payload: &'a str,
+}
+
+impl<'a> ITermFile<'a> {
+    fn parse(value: &'a str) -> Option<ITermFile<'a>> {
+        let (args, payload) = value.split_once(':')?;
+        if base64::is_base64(payload) {
+            Some(ITermFile { args, payload })
+        } else {
+            None
+        }
+    }
+
+    ///The value of an argument, as it was written.
+    pub fn arg(&self, key: &str) -> Option<&'a str> {
+        self.args.split(';').find_map(|arg| {
+            let mut parts = arg.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) if name == key => Some(value),
+                _ => None,
+            }
+        })
+    }
+
+    ///The base64 encoded file name.
+    pub fn name(&self) -> Option<&'a str> {
+        self.arg("name")
+    }
+
+    ///Whether the file is to be shown in the terminal, rather than downloaded.
+    pub fn inline(&self) -> bool {
+        self.arg("inline") == Some("1")
+    }
+
+    ///The size in bytes, as announced by the sender.
+    pub fn size(&self) -> Option<u64> {
+        self.arg("size").and_then(|size| size.parse().ok())
+    }
+
+    ///An upper bound on the length of the decoded contents.
+    pub fn decoded_len(&self) -> usize {
+        base64::decoded_len(self.payload)
+    }
+
+    ///Decodes the contents into `out`, returning how many bytes were written. This fails on
+    ///malformed base64, or if `out` is too short.
+    pub fn decode_into(&self, out: &mut [u8]) -> Option<usize> {
+        base64::decode_into(self.payload, out)
+    }
+
+    ///Decodes the contents. This fails on malformed base64.
+    #[cfg(any(feature = "std", test))]
+    pub fn decode(&self) -> Option<std::vec::Vec<u8>> {
+        base64::decode(self.payload)
+    }
+
+    ///Decodes the file name. This fails if there is none, or if it isn't valid UTF-8.
+    #[cfg(any(feature = "std", test))]
+    pub fn decode_name(&self) -> Option<std::string::String> {
+        let name = base64::decode(self.name()?)?;
+        std::string::String::from_utf8(name).ok()
+    }
+}
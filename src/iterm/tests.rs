@@ -0,0 +1,56 @@
+use crate::*;
+
+#[test]
+fn test_command() {
+    assert_eq!(
+        ITermCommand::parse("SetMark"),
+        Some(ITermCommand {
+            key: "SetMark",
+            value: None
+        })
+    );
+    assert_eq!(
+        ITermCommand::parse("RemoteHost=user@host"),
+        Some(ITermCommand {
+            key: "RemoteHost",
+            value: Some("user@host")
+        })
+    );
+    assert_eq!(ITermCommand::parse(""), None);
+    assert_eq!(ITermCommand::parse("=x"), None);
+    assert_eq!(ITermCommand::parse("Set Mark"), None);
+
+    let var = ITermCommand::parse("SetUserVar=foo=YmFy").unwrap();
+    assert_eq!(var.user_var(), Some(("foo", "YmFy")));
+    assert_eq!(var.file(), None);
+    assert_eq!(
+        ITermCommand::parse("SetUserVar=foo").unwrap().user_var(),
+        None
+    );
+}
+
+#[test]
+fn test_file() {
+    let cmd = ITermCommand::parse("File=name=dGVzdC5wbmc=;size=5;inline=1:aGVsbG8=").unwrap();
+    let file = cmd.file().unwrap();
+    assert_eq!(file.name(), Some("dGVzdC5wbmc="));
+    assert_eq!(file.decode_name().as_deref(), Some("test.png"));
+    assert_eq!(file.size(), Some(5));
+    assert!(file.inline());
+    assert_eq!(file.arg("width"), None);
+    assert_eq!(file.decode().unwrap(), b"hello");
+
+    let mut short = [0; 4];
+    assert_eq!(file.decode_into(&mut short), None);
+
+    let file = ITermCommand::parse("File=:").unwrap().file().unwrap();
+    assert!(!file.inline());
+    assert_eq!(file.name(), None);
+    assert_eq!(file.decode().unwrap(), b"");
+
+    assert_eq!(ITermCommand::parse("File=inline=1").unwrap().file(), None);
+    assert_eq!(
+        ITermCommand::parse("File=inline=1:%%").unwrap().file(),
+        None
+    );
+}
@@ -0,0 +1,264 @@
+#[cfg(test)]
+mod tests;
+
+use crate::base64;
+
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+#[cfg(any(feature = "std", test))]
+use std::{string::String, vec::Vec};
+
+///What a kitty graphics command does, from its `a` key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GraphicsAction {
+    ///`t`, the default.
+    Transmit,
+    ///`T`
+    TransmitAndDisplay,
+    ///`q`
+    Query,
+    ///`p`
+    Put,
+    ///`d`
+    Delete,
+    ///`f`
+    Frame,
+    ///`a`
+    Animate,
+    ///`c`
+    Compose,
+    Unknown(char),
+}
+
+///The pixel format of the transmitted data, from the `f` key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GraphicsFormat {
+    ///`24`
+    Rgb,
+    ///`32`, the default.
+    Rgba,
+    ///`100`
+    Png,
+    Unknown(u32),
+}
+
+///How the image data is transmitted, from the `t` key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GraphicsMedium {
+    ///`d`, in the payload itself. The default.
+    Direct,
+    ///`f`, through a file whose path is the payload.
+    File,
+    ///`t`, through a temporary file that the terminal deletes afterwards.
+    TempFile,
+    ///`s`, through a shared memory object.
+    SharedMemory,
+    Unknown(char),
+}
+
+///A kitty graphics protocol command, sent as an APC string starting with `G`.
+///
+///The control data is kept as it was written, and its keys are read through the accessors,
+///which fill in the protocol's defaults.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KittyGraphics<'a> {
+    ///The `,` separated `key=value` pairs.
+    pub control: &'a str,
+    ///The base64 encoded payload, which follows a `;` if there is one.
+    pub payload: Option<&'a str>,
+}
+
+fn is_key_value(pair: &str) -> bool {
+    let mut chars = pair.chars();
+    match (chars.next(), chars.next()) {
+        (Some(key), Some('=')) => key.is_ascii_alphabetic(),
+        _ => false,
+    }
+}
+
+impl<'a> KittyGraphics<'a> {
+    ///Reads the data of an APC string, which has to start with `G`.
+    pub fn parse(data: &'a str) -> Option<KittyGraphics<'a>> {
+        let data = data.strip_prefix('G')?;
+        let mut parts = data.splitn(2, ';');
+        let control = parts.next().unwrap_or("");
+        let payload = parts.next();
+        let valid = (control.is_empty() || control.split(',').all(is_key_value))
+            && payload.into_iter().all(base64::is_base64);
+        if valid {
+            Some(KittyGraphics { control, payload })
+        } else {
+            None
+        }
+    }
+
+    ///The value of a control key, as it was written.
+    pub fn get(&self, key: char) -> Option<&'a str> {
+        self.control
+            .split(',')
+            .find_map(|pair| pair.strip_prefix(key).and_then(|val| val.strip_prefix('=')))
+    }
+
+    fn number(&self, key: char) -> Option<u32> {
+        self.get(key).and_then(|val| val.parse().ok())
+    }
+
+    fn single(&self, key: char) -> Option<char> {
+        self.get(key).and_then(|val| val.chars().next())
+    }
+
+    pub fn action(&self) -> GraphicsAction {
+        match self.single('a').unwrap_or('t') {
+            't' => GraphicsAction::Transmit,
+            'T' => GraphicsAction::TransmitAndDisplay,
+            'q' => GraphicsAction::Query,
+            'p' => GraphicsAction::Put,
+            'd' => GraphicsAction::Delete,
+            'f' => GraphicsAction::Frame,
+            'a' => GraphicsAction::Animate,
+            'c' => GraphicsAction::Compose,
+            other => GraphicsAction::Unknown(other),
+        }
+    }
+
+    pub fn format(&self) -> GraphicsFormat {
+        match self.number('f').unwrap_or(32) {
+            24 => GraphicsFormat::Rgb,
+            32 => GraphicsFormat::Rgba,
+            100 => GraphicsFormat::Png,
+            other => GraphicsFormat::Unknown(other),
+        }
+    }
+
+    pub fn medium(&self) -> GraphicsMedium {
+        match self.single('t').unwrap_or('d') {
+            'd' => GraphicsMedium::Direct,
+            'f' => GraphicsMedium::File,
+            't' => GraphicsMedium::TempFile,
+            's' => GraphicsMedium::SharedMemory,
+            other => GraphicsMedium::Unknown(other),
+        }
+    }
+
+    ///`i`, the image id chosen by the client.
+    pub fn id(&self) -> Option<u32> {
+        self.number('i')
+    }
+
+    ///`I`, the image number, which the terminal maps to an id of its own.
+    pub fn number_id(&self) -> Option<u32> {
+        self.number('I')
+    }
+
+    ///`p`, the placement id.
+    pub fn placement(&self) -> Option<u32> {
+        self.number('p')
+    }
+
+    ///`s` and `v`, the size in pixels of raw image data.
+    pub fn size(&self) -> (Option<u32>, Option<u32>) {
+        (self.number('s'), self.number('v'))
+    }
+
+    ///`c` and `r`, the number of columns and rows to display the image over.
+    pub fn cells(&self) -> (Option<u32>, Option<u32>) {
+        (self.number('c'), self.number('r'))
+    }
+
+    ///`o=z`, whether the data is compressed with zlib.
+    pub fn compressed(&self) -> bool {
+        self.get('o') == Some("z")
+    }
+
+    ///`m=1`, whether more chunks of this transmission follow.
+    pub fn more(&self) -> bool {
+        self.get('m') == Some("1")
+    }
+
+    ///`q`, which silences the terminal's replies: 1 for successes, 2 for errors too.
+    pub fn quiet(&self) -> u32 {
+        self.number('q').unwrap_or(0)
+    }
+}
+
+impl<'a> Display for KittyGraphics<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "G{}", self.control)?;
+        match self.payload {
+            Some(payload) => write!(formatter, ";{}", payload),
+            None => Ok(()),
+        }
+    }
+}
+
+///A kitty graphics transmission put back together from its chunks.
+#[cfg(any(feature = "std", test))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KittyImage {
+    ///The control data of the first chunk, which describes the whole transmission.
+    pub control: String,
+    ///The decoded payload.
+    pub data: Vec<u8>,
+}
+
+#[cfg(any(feature = "std", test))]
+impl KittyImage {
+    ///The control data, read as a command.
+    pub fn command(&self) -> KittyGraphics<'_> {
+        KittyGraphics {
+            control: &self.control,
+            payload: None,
+        }
+    }
+}
+
+///Joins transmissions that were split into chunks with `m=1`.
+#[cfg(any(feature = "std", test))]
+#[derive(Debug, Default)]
+pub struct KittyReassembler {
+    control: String,
+    payload: String,
+    active: bool,
+}
+
+#[cfg(any(feature = "std", test))]
+impl KittyReassembler {
+    pub fn new() -> KittyReassembler {
+        KittyReassembler::default()
+    }
+
+    ///Whether a chunked transmission has been started, but not finished.
+    pub fn is_pending(&self) -> bool {
+        self.active
+    }
+
+    ///Takes the next graphics command. Once the last chunk of a transmission arrives, or a
+    ///transmission that wasn't split at all, the whole transmission is returned. A
+    ///transmission whose payload isn't valid base64 is dropped.
+    ///
+    ///Only transmissions (`a=t`, `a=T` and `a=f`) give images. Other commands, such as
+    ///deletes, placements and queries, give `None`.
+    pub fn push(&mut self, command: &KittyGraphics<'_>) -> Option<KittyImage> {
+        if !self.active {
+            match command.action() {
+                GraphicsAction::Transmit
+                | GraphicsAction::TransmitAndDisplay
+                | GraphicsAction::Frame => (),
+                _ => return None,
+            }
+            self.control.clear();
+            self.control.push_str(command.control);
+            self.payload.clear();
+        }
+        self.payload.push_str(command.payload.unwrap_or(""));
+        self.active = command.more();
+        if self.active {
+            return None;
+        }
+
+        let data = base64::decode(&self.payload)?;
+        Some(KittyImage {
+            control: self.control.clone(),
+            data,
+        })
+    }
+}
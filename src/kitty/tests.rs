@@ -0,0 +1,80 @@
+use crate::*;
+
+#[test]
+fn test_keys() {
+    let cmd = KittyGraphics::parse("Gf=100,a=T,i=31,p=7,c=10,r=5,m=1;iVBO").unwrap();
+    assert_eq!(cmd.action(), GraphicsAction::TransmitAndDisplay);
+    assert_eq!(cmd.format(), GraphicsFormat::Png);
+    assert_eq!(cmd.medium(), GraphicsMedium::Direct);
+    assert_eq!(cmd.id(), Some(31));
+    assert_eq!(cmd.number_id(), None);
+    assert_eq!(cmd.placement(), Some(7));
+    assert_eq!(cmd.cells(), (Some(10), Some(5)));
+    assert_eq!(cmd.size(), (None, None));
+    assert!(cmd.more());
+    assert!(!cmd.compressed());
+    assert_eq!(cmd.payload, Some("iVBO"));
+
+    let cmd = KittyGraphics::parse("Ga=d").unwrap();
+    assert_eq!(cmd.action(), GraphicsAction::Delete);
+    assert_eq!(cmd.format(), GraphicsFormat::Rgba);
+    assert_eq!(cmd.payload, None);
+    assert_eq!(cmd.quiet(), 0);
+
+    assert_eq!(
+        KittyGraphics::parse("G").unwrap().action(),
+        GraphicsAction::Transmit
+    );
+    assert_eq!(KittyGraphics::parse("Xa=d"), None);
+    assert_eq!(KittyGraphics::parse("Ga=d,oops"), None);
+    assert_eq!(KittyGraphics::parse("Gab=d"), None);
+    assert_eq!(KittyGraphics::parse("Ga=T;not base64"), None);
+
+    // Built by hand, so the control data was never checked.
+    let cmd = KittyGraphics {
+        control: "a,ab=d,é,i=5",
+        payload: None,
+    };
+    assert_eq!(cmd.action(), GraphicsAction::Transmit);
+    assert_eq!(cmd.id(), Some(5));
+}
+
+#[test]
+fn test_reassembly() {
+    let input = "\x1b_Gf=100,a=T,i=1,m=1;aGVs\x1b\\\x1b_Gm=1;bG8g\x1b\\\x1b_Gm=0;d29ybGQ=\x1b\\";
+
+    let mut reassembler = KittyReassembler::new();
+    let mut images = vec![];
+    for out in input.ansi_parse() {
+        if let Output::Escape(AnsiSequence::KittyGraphics(cmd, _)) = out {
+            images.extend(reassembler.push(&cmd));
+        }
+    }
+
+    assert!(!reassembler.is_pending());
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].data, b"hello world");
+    assert_eq!(images[0].command().format(), GraphicsFormat::Png);
+    assert_eq!(images[0].command().id(), Some(1));
+
+    // A transmission that isn't chunked comes out straight away.
+    let cmd = KittyGraphics::parse("Ga=t,i=2,s=1,v=1,f=24;AAAA").unwrap();
+    let image = reassembler.push(&cmd).unwrap();
+    assert_eq!(image.data, [0, 0, 0]);
+    assert_eq!(image.control, "a=t,i=2,s=1,v=1,f=24");
+
+    // Commands that don't transmit anything aren't images.
+    for cmd in &["Ga=d,d=i,i=2", "Ga=p,i=2", "Ga=q,i=3,s=1,v=1,f=24;AAAA"] {
+        let cmd = KittyGraphics::parse(cmd).unwrap();
+        assert_eq!(reassembler.push(&cmd), None);
+        assert!(!reassembler.is_pending());
+    }
+
+    // Padding in the middle of the joined payload.
+    let cmd = KittyGraphics::parse("Gm=1;AAA=").unwrap();
+    assert_eq!(reassembler.push(&cmd), None);
+    assert!(reassembler.is_pending());
+    let cmd = KittyGraphics::parse("Gm=0;AAAA").unwrap();
+    assert_eq!(reassembler.push(&cmd), None);
+    assert!(!reassembler.is_pending());
+}
@@ -1,23 +1,89 @@
 #![recursion_limit = "256"]
 #![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+mod base64;
+mod clipboard;
+mod dcs;
+mod device;
 mod enums;
+mod input;
+mod iterm;
+mod kitty;
+mod modes;
+mod mouse;
+mod palette;
 mod parsers;
+#[cfg(any(feature = "std", test))]
+mod probe;
+mod sgr;
+mod shell;
+#[cfg(any(feature = "std", test))]
+mod sixel;
+mod stream;
 mod traits;
 
 ///This is a library for parsing ANSI escape sequences. Currently all the basic escape sequences
 ///are implemented:
 /// + Cursor Position
 /// + Cursor {Up, Down, Forward, Backward}
-/// + Cursor {Save, Restore}
-/// + Erase Display
-/// + Erase Line
-/// + Set Graphics mode
+/// + Cursor {Save, Restore}, along with the DEC `ESC 7` and `ESC 8` forms
+/// + Cursor {Next Line, Previous Line, Column}, absolute and relative line and character
+///   positions, and tabulation
+/// + Erase in Display and Line, along with their selective forms
+/// + Erase Characters
+/// + Insert and Delete Characters and Lines, Scroll Up and Down, Repeat
+/// + Index, Next Line, Reverse Index, Tab Set, Full Reset and the screen alignment test
+/// + Set Graphics mode, with typed colors and attributes
 /// + Set and Reset Text Mode
+/// + Set and Reset of DEC private and ANSI modes, any number at a time
+/// + Mode requests and reports (DECRQM and DECRPM)
+/// + Device attributes (DA1, DA2 and DA3), status and cursor position reports (DSR and CPR),
+///   and the terminal version (XTVERSION), both the requests and the replies
+/// + Window title and icon name (OSC 0, 1 and 2)
+/// + Hyperlinks (OSC 8)
+/// + Palette and default colors (OSC 4, 10, 11, 12 and their resets)
+/// + Clipboard access (OSC 52)
+/// + Shell integration marks and working directory reports (OSC 133, 633, 7 and 1337)
+/// + Setting and terminfo queries (DECRQSS and XTGETTCAP) and their replies
+/// + Kitty graphics commands (APC G)
+/// + iTerm2 commands and inline files (OSC 1337)
 ///
 /// This is done through a pulldown type parser, where an iterator is exposed. This essentially
 /// turns all of the ANSI sequences into enums and splits the string at every location that there
-/// was an ANSI Sequence.
+/// was an ANSI Sequence. Well-formed sequences that aren't implemented are still split out, as
+/// an uninterpreted `Sequence`.
+///
+/// With the `std` feature, sixel images found in the output can be decoded to RGBA through
+/// `SixelImage`, and chunked kitty graphics transmissions put back together through
+/// `KittyReassembler`. A `TerminalProbe` sends queries to a terminal over any reader and writer
+/// pair, and reads back the answers.
+///
+/// What a terminal sends as input is read differently, as `ESC [ A` is the up arrow there
+/// rather than a cursor movement. `parse_input` and `AnsiInputParser` turn input into key
+/// presses, mouse reports, bracketed pastes and focus changes, and `MouseEvent` can write
+/// mouse reports back out for replaying them. With the `std` feature, input that arrives in
+/// pieces can be fed through an `InputStreamParser`.
+///
+/// Raw bytes can be parsed through `AnsiByteParser` without decoding them first, which also
+/// understands 8-bit C1 controls. Bytes that arrive in pieces can be fed through an
+/// `AnsiStreamParser`, which holds on to sequences and characters that are split between reads.
+pub use base64::Base64;
+pub use clipboard::*;
+pub use dcs::*;
+pub use device::*;
 pub use enums::*;
-pub use parsers::parse_escape;
+pub use input::*;
+pub use iterm::*;
+pub use kitty::*;
+pub use modes::*;
+pub use mouse::*;
+pub use palette::*;
+pub use parsers::{parse_escape, parse_sequence};
+#[cfg(any(feature = "std", test))]
+pub use probe::*;
+pub use sgr::*;
+pub use shell::*;
+#[cfg(any(feature = "std", test))]
+pub use sixel::*;
+pub use stream::*;
 pub use traits::*;
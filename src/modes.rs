@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests;
+
+use crate::AnsiSequence;
+
+use heapless::{consts::U16, Vec};
+
+///The DEC private modes, set and reset through `CSI ? Pm h` and `CSI ? Pm l`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DecMode {
+    ///1, DECCKM
+    CursorKeys,
+    ///2, DECANM, which switches to VT52 mode when reset.
+    Ansi,
+    ///3, DECCOLM
+    Column132,
+    ///4, DECSCLM
+    SmoothScroll,
+    ///5, DECSCNM
+    ReverseVideo,
+    ///6, DECOM
+    Origin,
+    ///7, DECAWM
+    AutoWrap,
+    ///8, DECARM
+    AutoRepeat,
+    ///9, interlacing on the VT100, which xterm took over for X10 mouse reporting.
+    X10Mouse,
+    ///12
+    BlinkingCursor,
+    ///25, DECTCEM
+    ShowCursor,
+    ///47
+    AltScreen,
+    ///1000
+    MouseClicks,
+    ///1002
+    MouseDrag,
+    ///1003
+    MouseMotion,
+    ///1004
+    FocusEvents,
+    ///1005
+    Utf8Mouse,
+    ///1006
+    SgrMouse,
+    ///1015
+    UrxvtMouse,
+    ///1016
+    SgrPixelMouse,
+    ///1047
+    AltScreenClear,
+    ///1048
+    SaveCursor,
+    ///1049, which saves the cursor and switches to a cleared alternate screen.
+    AltScreenSaveCursor,
+    ///2004
+    BracketedPaste,
+    ///2026
+    SynchronizedOutput,
+    Other(u16),
+}
+
+impl DecMode {
+    pub fn code(self) -> u16 {
+        use DecMode::*;
+        match self {
+            CursorKeys => 1,
+            Ansi => 2,
+            Column132 => 3,
+            SmoothScroll => 4,
+            ReverseVideo => 5,
+            Origin => 6,
+            AutoWrap => 7,
+            AutoRepeat => 8,
+            X10Mouse => 9,
+            BlinkingCursor => 12,
+            ShowCursor => 25,
+            AltScreen => 47,
+            MouseClicks => 1000,
+            MouseDrag => 1002,
+            MouseMotion => 1003,
+            FocusEvents => 1004,
+            Utf8Mouse => 1005,
+            SgrMouse => 1006,
+            UrxvtMouse => 1015,
+            SgrPixelMouse => 1016,
+            AltScreenClear => 1047,
+            SaveCursor => 1048,
+            AltScreenSaveCursor => 1049,
+            BracketedPaste => 2004,
+            SynchronizedOutput => 2026,
+            Other(code) => code,
+        }
+    }
+}
+
+impl From<u16> for DecMode {
+    fn from(code: u16) -> Self {
+        use DecMode::*;
+        match code {
+            1 => CursorKeys,
+            2 => Ansi,
+            3 => Column132,
+            4 => SmoothScroll,
+            5 => ReverseVideo,
+            6 => Origin,
+            7 => AutoWrap,
+            8 => AutoRepeat,
+            9 => X10Mouse,
+            12 => BlinkingCursor,
+            25 => ShowCursor,
+            47 => AltScreen,
+            1000 => MouseClicks,
+            1002 => MouseDrag,
+            1003 => MouseMotion,
+            1004 => FocusEvents,
+            1005 => Utf8Mouse,
+            1006 => SgrMouse,
+            1015 => UrxvtMouse,
+            1016 => SgrPixelMouse,
+            1047 => AltScreenClear,
+            1048 => SaveCursor,
+            1049 => AltScreenSaveCursor,
+            2004 => BracketedPaste,
+            2026 => SynchronizedOutput,
+            code => Other(code),
+        }
+    }
+}
+
+///The ANSI modes, set and reset through `CSI Pm h` and `CSI Pm l`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AnsiMode {
+    ///2, KAM
+    KeyboardLock,
+    ///4, IRM
+    Insert,
+    ///12, SRM, which turns local echo off when set.
+    SendReceive,
+    ///20, LNM
+    NewLine,
+    Other(u16),
+}
+
+impl AnsiMode {
+    pub fn code(self) -> u16 {
+        match self {
+            AnsiMode::KeyboardLock => 2,
+            AnsiMode::Insert => 4,
+            AnsiMode::SendReceive => 12,
+            AnsiMode::NewLine => 20,
+            AnsiMode::Other(code) => code,
+        }
+    }
+}
+
+impl From<u16> for AnsiMode {
+    fn from(code: u16) -> Self {
+        match code {
+            2 => AnsiMode::KeyboardLock,
+            4 => AnsiMode::Insert,
+            12 => AnsiMode::SendReceive,
+            20 => AnsiMode::NewLine,
+            code => AnsiMode::Other(code),
+        }
+    }
+}
+
+///How a terminal reports a mode in reply to a request, through DECRPM.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ModeState {
+    ///0
+    NotRecognized,
+    ///1
+    Set,
+    ///2
+    Reset,
+    ///3
+    PermanentlySet,
+    ///4
+    PermanentlyReset,
+}
+
+impl ModeState {
+    pub fn code(self) -> u8 {
+        match self {
+            ModeState::NotRecognized => 0,
+            ModeState::Set => 1,
+            ModeState::Reset => 2,
+            ModeState::PermanentlySet => 3,
+            ModeState::PermanentlyReset => 4,
+        }
+    }
+
+    pub(crate) fn from_code(code: u8) -> Option<ModeState> {
+        match code {
+            0 => Some(ModeState::NotRecognized),
+            1 => Some(ModeState::Set),
+            2 => Some(ModeState::Reset),
+            3 => Some(ModeState::PermanentlySet),
+            4 => Some(ModeState::PermanentlyReset),
+            _ => None,
+        }
+    }
+
+    ///Whether the mode is set, whether or not it can be changed.
+    pub fn is_set(self) -> bool {
+        matches!(self, ModeState::Set | ModeState::PermanentlySet)
+    }
+
+    ///Whether the terminal knows the mode at all.
+    pub fn is_recognized(self) -> bool {
+        self != ModeState::NotRecognized
+    }
+}
+
+fn single<T>(mode: T, set: bool) -> Option<(Vec<T, U16>, bool)> {
+    let mut modes = Vec::new();
+    modes.push(mode).ok()?;
+    Some((modes, set))
+}
+
+impl<'a> AnsiSequence<'a> {
+    ///The DEC private modes this sets or resets, along with whether they are set. This covers
+    ///the variants for single modes, such as `HideCursor`, as well as `DecPrivateMode`.
+    pub fn dec_private_modes(&self) -> Option<(Vec<DecMode, U16>, bool)> {
+        use AnsiSequence::*;
+        match self {
+            DecPrivateMode(modes, set) => Some((modes.clone(), *set)),
+            CursorToApp => single(DecMode::CursorKeys, true),
+            SetCursorKeyToCursor => single(DecMode::CursorKeys, false),
+            SetVT52 => single(DecMode::Ansi, false),
+            SetCol132 => single(DecMode::Column132, true),
+            SetCol80 => single(DecMode::Column132, false),
+            SetSmoothScroll => single(DecMode::SmoothScroll, true),
+            SetJumpScrolling => single(DecMode::SmoothScroll, false),
+            SetReverseVideo => single(DecMode::ReverseVideo, true),
+            SetNormalVideo => single(DecMode::ReverseVideo, false),
+            SetOriginRelative => single(DecMode::Origin, true),
+            SetOriginAbsolute => single(DecMode::Origin, false),
+            SetAutoWrap => single(DecMode::AutoWrap, true),
+            ResetAutoWrap => single(DecMode::AutoWrap, false),
+            SetAutoRepeat => single(DecMode::AutoRepeat, true),
+            ResetAutoRepeat => single(DecMode::AutoRepeat, false),
+            SetInterlacing => single(DecMode::X10Mouse, true),
+            ResetInterlacing => single(DecMode::X10Mouse, false),
+            ShowCursor => single(DecMode::ShowCursor, true),
+            HideCursor => single(DecMode::ShowCursor, false),
+            _ => None,
+        }
+    }
+
+    ///The ANSI modes this sets or resets, along with whether they are set. This covers
+    ///`SetNewLineMode` and `SetLineFeedMode` as well as `AnsiMode`.
+    pub fn ansi_modes(&self) -> Option<(Vec<AnsiMode, U16>, bool)> {
+        use AnsiSequence::*;
+        match self {
+            AnsiMode(modes, set) => Some((modes.clone(), *set)),
+            SetNewLineMode => single(crate::AnsiMode::NewLine, true),
+            SetLineFeedMode => single(crate::AnsiMode::NewLine, false),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::*;
+
+#[test]
+fn test_codes() {
+    for code in 0..3000 {
+        assert_eq!(DecMode::from(code).code(), code);
+        assert_eq!(AnsiMode::from(code).code(), code);
+    }
+    assert_eq!(DecMode::from(2026), DecMode::SynchronizedOutput);
+    assert_eq!(DecMode::from(1001), DecMode::Other(1001));
+    assert_eq!(AnsiMode::from(4), AnsiMode::Insert);
+}
+
+#[test]
+fn test_conveniences() {
+    let (modes, set) = AnsiSequence::HideCursor.dec_private_modes().unwrap();
+    assert_eq!((&modes[..], set), (&[DecMode::ShowCursor][..], false));
+    let (modes, set) = AnsiSequence::SetInterlacing.dec_private_modes().unwrap();
+    assert_eq!((&modes[..], set), (&[DecMode::X10Mouse][..], true));
+    let (modes, set) = AnsiSequence::SetLineFeedMode.ansi_modes().unwrap();
+    assert_eq!((&modes[..], set), (&[AnsiMode::NewLine][..], false));
+
+    assert_eq!(AnsiSequence::SetLineFeedMode.dec_private_modes(), None);
+    assert_eq!(AnsiSequence::HideCursor.ansi_modes(), None);
+    assert_eq!(AnsiSequence::SetMode(7).dec_private_modes(), None);
+}
+
+#[test]
+fn test_display() {
+    let mut modes = heapless::Vec::new();
+    modes.push(DecMode::MouseClicks).unwrap();
+    modes.push(DecMode::SgrMouse).unwrap();
+    assert_eq!(
+        AnsiSequence::DecPrivateMode(modes.clone(), true).to_string(),
+        "\u{1b}[?1000;1006h"
+    );
+    let parsed: Vec<_> = "\u{1b}[?1000;1006l".ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        [Output::Escape(AnsiSequence::DecPrivateMode(modes, false))]
+    );
+}
+
+#[test]
+fn test_mode_state() {
+    for code in 0..5 {
+        assert_eq!(ModeState::from_code(code).unwrap().code(), code);
+    }
+    assert_eq!(ModeState::from_code(5), None);
+
+    assert!(ModeState::PermanentlySet.is_set());
+    assert!(!ModeState::Reset.is_set());
+    assert!(!ModeState::NotRecognized.is_set());
+    assert!(ModeState::PermanentlyReset.is_recognized());
+    assert!(!ModeState::NotRecognized.is_recognized());
+}
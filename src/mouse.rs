@@ -0,0 +1,256 @@
+#[cfg(test)]
+mod tests;
+
+use crate::Modifiers;
+
+use core::{convert::TryFrom, str};
+#[cfg(any(feature = "std", test))]
+use std::vec::Vec;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    WheelUp,
+    WheelDown,
+    WheelLeft,
+    WheelRight,
+    ///Button 8, usually the side button for going back.
+    Back,
+    ///Button 9, usually the side button for going forward.
+    Forward,
+    ///Buttons 10 and 11.
+    Other(u8),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MouseAction {
+    ///A button was pressed, or a wheel turned.
+    Press(MouseButton),
+    ///A button was let go. Only SGR reports say which one it was.
+    Release(Option<MouseButton>),
+    ///The mouse moved with a button held down.
+    Drag(MouseButton),
+    ///The mouse moved with no button held down, which is only reported in any event mode.
+    Motion,
+}
+
+///How mouse reports are written, which follows from the modes that turned reporting on.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MouseEncoding {
+    ///`CSI M Cb Cx Cy`, with each value as a single byte offset by 32. Positions past 223
+    ///can't be sent.
+    X10,
+    ///`CSI < Cb ; Cx ; Cy M`, or `m` for a release. Mode 1006.
+    Sgr,
+    ///`CSI Cb ; Cx ; Cy M`, with the button offset by 32. Mode 1015.
+    Urxvt,
+    ///Written like `Sgr`, but the position is in pixels. Mode 1016.
+    SgrPixels,
+}
+
+///A mouse report.
+///
+///The position starts at 1, 1 in the top left. With SGR pixel reporting (mode 1016) it is in
+///pixels rather than cells. Those reports are written just like SGR ones, so which of the two
+///they are is up to the modes that were set.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MouseEvent {
+    pub action: MouseAction,
+    ///Shift, alt and ctrl. Terminals often keep some of these for themselves, such as shift
+    ///for selecting text.
+    pub modifiers: Modifiers,
+    pub column: u32,
+    pub row: u32,
+}
+
+fn button_from_code(code: u16) -> MouseButton {
+    match (code & 0xc0, code & 3) {
+        (0, 0) => MouseButton::Left,
+        (0, 1) => MouseButton::Middle,
+        (0, 2) => MouseButton::Right,
+        (0x40, 0) => MouseButton::WheelUp,
+        (0x40, 1) => MouseButton::WheelDown,
+        (0x40, 2) => MouseButton::WheelLeft,
+        (0x40, _) => MouseButton::WheelRight,
+        (0x80, 0) => MouseButton::Back,
+        (0x80, 1) => MouseButton::Forward,
+        (_, low) => MouseButton::Other(8 + low as u8),
+    }
+}
+
+fn button_code(button: MouseButton) -> u16 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+        MouseButton::WheelUp => 0x40,
+        MouseButton::WheelDown => 0x41,
+        MouseButton::WheelLeft => 0x42,
+        MouseButton::WheelRight => 0x43,
+        MouseButton::Back => 0x80,
+        MouseButton::Forward => 0x81,
+        MouseButton::Other(num) => 0x80 | (u16::from(num.saturating_sub(8)) & 3),
+    }
+}
+
+impl MouseEvent {
+    //Reads the button code that all encodings share. The release flag is for SGR reports,
+    //which end in `m` rather than setting the button to 3.
+    pub(crate) fn from_code(code: u16, column: u32, row: u32, release: bool) -> MouseEvent {
+        let modifiers = Modifiers {
+            shift: code & 4 != 0,
+            alt: code & 8 != 0,
+            ctrl: code & 16 != 0,
+            meta: false,
+        };
+        let no_button = code & 0xc3 == 3;
+        let action = match (release, code & 32 != 0, no_button) {
+            (true, _, true) => MouseAction::Release(None),
+            (true, _, false) => MouseAction::Release(Some(button_from_code(code))),
+            (false, true, true) => MouseAction::Motion,
+            (false, true, false) => MouseAction::Drag(button_from_code(code)),
+            (false, false, true) => MouseAction::Release(None),
+            (false, false, false) => MouseAction::Press(button_from_code(code)),
+        };
+        MouseEvent {
+            action,
+            modifiers,
+            column,
+            row,
+        }
+    }
+
+    ///The button code of this event, as SGR reports write it.
+    pub fn code(&self) -> u16 {
+        let button = match self.action {
+            MouseAction::Press(button) => button_code(button),
+            MouseAction::Drag(button) => button_code(button) | 32,
+            MouseAction::Release(Some(button)) => button_code(button),
+            MouseAction::Release(None) => 3,
+            MouseAction::Motion => 3 | 32,
+        };
+        button
+            | (self.modifiers.shift as u16) << 2
+            | (self.modifiers.alt as u16) << 3
+            | (self.modifiers.ctrl as u16) << 4
+    }
+
+    ///Writes this event as a terminal would report it, returning how many bytes were written.
+    ///This fails if `out` is too short, or if the position can't be written in X10 form.
+    ///
+    ///Only SGR reports say which button was let go, the others send a release as button 3.
+    pub fn encode_into(&self, encoding: MouseEncoding, out: &mut [u8]) -> Option<usize> {
+        let release = matches!(self.action, MouseAction::Release(_));
+        let code = match (encoding, release) {
+            (MouseEncoding::X10, true) | (MouseEncoding::Urxvt, true) => (self.code() & !0xc3) | 3,
+            _ => self.code(),
+        };
+
+        let mut writer = Writer { out, len: 0 };
+        match encoding {
+            MouseEncoding::X10 => {
+                writer.bytes(b"\x1b[M")?;
+                for val in &[u32::from(code), self.column, self.row] {
+                    let byte = val.checked_add(32).filter(|&byte| byte <= 0xff)?;
+                    writer.bytes(&[byte as u8])?;
+                }
+            }
+            MouseEncoding::Sgr | MouseEncoding::SgrPixels => {
+                writer.bytes(b"\x1b[<")?;
+                writer.number(u32::from(code))?;
+                writer.bytes(b";")?;
+                writer.number(self.column)?;
+                writer.bytes(b";")?;
+                writer.number(self.row)?;
+                writer.bytes(if release { b"m" } else { b"M" })?;
+            }
+            MouseEncoding::Urxvt => {
+                writer.bytes(b"\x1b[")?;
+                writer.number(u32::from(code) + 32)?;
+                writer.bytes(b";")?;
+                writer.number(self.column)?;
+                writer.bytes(b";")?;
+                writer.number(self.row)?;
+                writer.bytes(b"M")?;
+            }
+        }
+        Some(writer.len)
+    }
+
+    ///Writes this event as a terminal would report it. This fails if the position can't be
+    ///written in X10 form.
+    #[cfg(any(feature = "std", test))]
+    pub fn encode(&self, encoding: MouseEncoding) -> Option<Vec<u8>> {
+        //The longest report has three 10 digit numbers.
+        let mut out = [0; 40];
+        let len = self.encode_into(encoding, &mut out)?;
+        Some(out[..len].to_vec())
+    }
+}
+
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn bytes(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len + bytes.len();
+        self.out.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+
+    fn number(&mut self, mut val: u32) -> Option<()> {
+        let mut digits = [0; 10];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (val % 10) as u8;
+            val /= 10;
+            if val == 0 {
+                break;
+            }
+        }
+        self.bytes(&digits[start..])
+    }
+}
+
+//`CSI M` followed by three bytes.
+pub(crate) fn parse_x10(data: &[u8]) -> Option<MouseEvent> {
+    let val = |byte: u8| byte.checked_sub(32);
+    let code = val(data[0])?;
+    Some(MouseEvent::from_code(
+        code.into(),
+        val(data[1])?.into(),
+        val(data[2])?.into(),
+        false,
+    ))
+}
+
+//The parameters and final byte of an SGR or URXVT report.
+pub(crate) fn parse_params(params: &[u8], final_byte: u8) -> Option<MouseEvent> {
+    let params = str::from_utf8(params).ok()?;
+    let (sgr, params) = match params.strip_prefix('<') {
+        Some(params) => (true, params),
+        None => (false, params),
+    };
+    let mut nums = params.splitn(3, ';').map(|num| num.parse::<u32>().ok());
+    let code = nums.next()??;
+    let column = nums.next()??;
+    let row = nums.next()??;
+
+    match (sgr, final_byte) {
+        (true, b'M') | (true, b'm') => {
+            let code = u16::try_from(code).ok()?;
+            Some(MouseEvent::from_code(code, column, row, final_byte == b'm'))
+        }
+        (false, b'M') => {
+            let code = u16::try_from(code.checked_sub(32)?).ok()?;
+            Some(MouseEvent::from_code(code, column, row, false))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,165 @@
+use crate::*;
+
+use std::vec::Vec;
+
+fn mouse(input: &[u8]) -> Vec<MouseEvent> {
+    input
+        .input_parse()
+        .map(|event| match event {
+            InputEvent::Mouse(event) => event,
+            other => panic!("{:?}", other),
+        })
+        .collect()
+}
+
+fn event(action: MouseAction, column: u32, row: u32) -> MouseEvent {
+    MouseEvent {
+        action,
+        modifiers: Modifiers::NONE,
+        column,
+        row,
+    }
+}
+
+#[test]
+fn test_sgr() {
+    assert_eq!(
+        mouse(b"\x1b[<0;12;5M\x1b[<0;12;5m\x1b[<34;13;6M\x1b[<35;14;6M\x1b[<65;1;1M"),
+        [
+            event(MouseAction::Press(MouseButton::Left), 12, 5),
+            event(MouseAction::Release(Some(MouseButton::Left)), 12, 5),
+            event(MouseAction::Drag(MouseButton::Right), 13, 6),
+            event(MouseAction::Motion, 14, 6),
+            event(MouseAction::Press(MouseButton::WheelDown), 1, 1),
+        ]
+    );
+
+    let ctrl_click = mouse(b"\x1b[<17;300;200M")[0];
+    assert_eq!(ctrl_click.action, MouseAction::Press(MouseButton::Middle));
+    assert_eq!(ctrl_click.modifiers, Modifiers::CTRL);
+    assert_eq!((ctrl_click.column, ctrl_click.row), (300, 200));
+
+    assert_eq!(
+        mouse(b"\x1b[<128;1;1M\x1b[<129;1;1M\x1b[<130;1;1M")
+            .iter()
+            .map(|event| event.action)
+            .collect::<Vec<_>>(),
+        [
+            MouseAction::Press(MouseButton::Back),
+            MouseAction::Press(MouseButton::Forward),
+            MouseAction::Press(MouseButton::Other(10)),
+        ]
+    );
+}
+
+#[test]
+fn test_x10_and_urxvt() {
+    assert_eq!(
+        mouse(b"\x1b[M !!\x1b[M#!!\x1b[M,\xff\xff\x1b[32;12;5M\x1b[35;12;5M"),
+        [
+            event(MouseAction::Press(MouseButton::Left), 1, 1),
+            event(MouseAction::Release(None), 1, 1),
+            MouseEvent {
+                action: MouseAction::Press(MouseButton::Left),
+                modifiers: Modifiers {
+                    shift: true,
+                    alt: true,
+                    ..Modifiers::NONE
+                },
+                column: 223,
+                row: 223,
+            },
+            event(MouseAction::Press(MouseButton::Left), 12, 5),
+            event(MouseAction::Release(None), 12, 5),
+        ]
+    );
+}
+
+#[test]
+fn test_malformed() {
+    assert_eq!(
+        b"\x1b[<0;1M\x1b[<99999999;1;1M\x1b[1;1;1M\x1b[M\x10!!"
+            .input_parse()
+            .collect::<Vec<_>>(),
+        [
+            InputEvent::Unknown(b"\x1b[<0;1M"),
+            InputEvent::Unknown(b"\x1b[<99999999;1;1M"),
+            InputEvent::Unknown(b"\x1b[1;1;1M"),
+            InputEvent::Unknown(b"\x1b[M\x10!!"),
+        ]
+    );
+    assert!(parse_input(b"\x1b[M !").is_err());
+}
+
+#[test]
+fn test_encode() {
+    let drag = MouseEvent {
+        action: MouseAction::Drag(MouseButton::Left),
+        modifiers: Modifiers::CTRL,
+        column: 12,
+        row: 5,
+    };
+    assert_eq!(drag.code(), 48);
+    assert_eq!(drag.encode(MouseEncoding::Sgr).unwrap(), b"\x1b[<48;12;5M");
+    assert_eq!(drag.encode(MouseEncoding::Urxvt).unwrap(), b"\x1b[80;12;5M");
+    assert_eq!(drag.encode(MouseEncoding::X10).unwrap(), b"\x1b[MP,%");
+
+    let release = event(MouseAction::Release(Some(MouseButton::Right)), 1000, 2000);
+    assert_eq!(
+        release.encode(MouseEncoding::SgrPixels).unwrap(),
+        b"\x1b[<2;1000;2000m"
+    );
+    assert_eq!(
+        release.encode(MouseEncoding::Urxvt).unwrap(),
+        b"\x1b[35;1000;2000M"
+    );
+    assert_eq!(release.encode(MouseEncoding::X10), None);
+
+    let mut short = [0; 8];
+    assert_eq!(drag.encode_into(MouseEncoding::Sgr, &mut short), None);
+    assert_eq!(drag.encode_into(MouseEncoding::X10, &mut short), Some(6));
+}
+
+#[test]
+fn test_round_trip() {
+    let buttons = [
+        MouseButton::Left,
+        MouseButton::Middle,
+        MouseButton::Right,
+        MouseButton::WheelUp,
+        MouseButton::WheelDown,
+        MouseButton::WheelLeft,
+        MouseButton::WheelRight,
+        MouseButton::Back,
+        MouseButton::Forward,
+        MouseButton::Other(10),
+        MouseButton::Other(11),
+    ];
+    let mut events = vec![
+        event(MouseAction::Motion, 40, 2),
+        event(MouseAction::Release(None), 40, 2),
+    ];
+    for &button in &buttons {
+        events.push(event(MouseAction::Press(button), 7, 9));
+        events.push(event(MouseAction::Drag(button), 8, 9));
+        events.push(event(MouseAction::Release(Some(button)), 8, 9));
+    }
+
+    for event in &events {
+        let sgr = event.encode(MouseEncoding::Sgr).unwrap();
+        assert_eq!(mouse(&sgr), [*event]);
+
+        //Without SGR, which button was let go is lost.
+        let expected = match event.action {
+            MouseAction::Release(_) => MouseEvent {
+                action: MouseAction::Release(None),
+                ..*event
+            },
+            _ => *event,
+        };
+        for &encoding in &[MouseEncoding::X10, MouseEncoding::Urxvt] {
+            let report = event.encode(encoding).unwrap();
+            assert_eq!(mouse(&report), [expected]);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+#[cfg(test)]
+mod tests;
+
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+
+///One channel of an X11 RGB color, kept with the number of hex digits it was written with.
+///
+///X11 scales a channel by the number of digits, so `f`, `ff` and `ffff` are all full
+///intensity.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Channel {
+    value: u16,
+    digits: u8,
+}
+
+impl Channel {
+    ///A channel written with four hex digits, as terminals reply with.
+    pub fn new(value: u16) -> Channel {
+        Channel { value, digits: 4 }
+    }
+
+    ///A channel written with `digits` hex digits. Fails unless there are between 1 and 4
+    ///digits, and `value` can be written with that many.
+    pub fn with_digits(value: u16, digits: u8) -> Option<Channel> {
+        let channel = Channel { value, digits };
+        if (1..=4).contains(&digits) && u32::from(value) <= channel.max() {
+            Some(channel)
+        } else {
+            None
+        }
+    }
+
+    pub fn value(self) -> u16 {
+        self.value
+    }
+
+    ///Between 1 and 4.
+    pub fn digits(self) -> u8 {
+        self.digits
+    }
+
+    fn max(self) -> u32 {
+        (1 << (4 * u32::from(self.digits))) - 1
+    }
+
+    ///The channel scaled to 16 bits.
+    pub fn to_u16(self) -> u16 {
+        (u32::from(self.value) * 0xffff / self.max()) as u16
+    }
+
+    ///The channel scaled to 8 bits.
+    pub fn to_u8(self) -> u8 {
+        ((u32::from(self.value) * 0xff + self.max() / 2) / self.max()) as u8
+    }
+
+    fn parse(hex: &str) -> Option<Channel> {
+        if hex.is_empty() || hex.len() > 4 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(Channel {
+            value: u16::from_str_radix(hex, 16).ok()?,
+            digits: hex.len() as u8,
+        })
+    }
+}
+
+impl From<u8> for Channel {
+    fn from(val: u8) -> Self {
+        Channel::new(u16::from(val) * 0x101)
+    }
+}
+
+impl Display for Channel {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{:01$x}", self.value, usize::from(self.digits))
+    }
+}
+
+///An RGB color in one of the X11 formats.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RgbColor {
+    pub red: Channel,
+    pub green: Channel,
+    pub blue: Channel,
+}
+
+impl RgbColor {
+    ///An 8 bit per channel color, written with four digits per channel.
+    pub fn new(red: u8, green: u8, blue: u8) -> RgbColor {
+        RgbColor {
+            red: red.into(),
+            green: green.into(),
+            blue: blue.into(),
+        }
+    }
+
+    ///The color scaled to 8 bits per channel.
+    pub fn to_rgb8(self) -> (u8, u8, u8) {
+        (self.red.to_u8(), self.green.to_u8(), self.blue.to_u8())
+    }
+}
+
+///The color in an OSC 4, 10, 11 or 12 sequence.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ColorSpec<'a> {
+    ///`?`, asking the terminal to report the color.
+    Query,
+    ///`rgb:R/G/B`, with 1 to 4 hex digits per channel.
+    Rgb(RgbColor),
+    ///The older `#RGB` form, with the same number of hex digits (1 to 4) for each channel.
+    Hash(RgbColor),
+    ///Anything else that is handed to the terminal as is, such as a color name (`red`) or
+    ///an `rgbi:` color.
+    Other(&'a str),
+}
+
+impl<'a> ColorSpec<'a> {
+    ///Reads a color spec. This never fails, as anything that isn't recognized is kept as
+    ///`Other`.
+    pub fn parse(spec: &'a str) -> ColorSpec<'a> {
+        let parsed = if spec == "?" {
+            Some(ColorSpec::Query)
+        } else if let Some(rgb) = spec.strip_prefix("rgb:") {
+            parse_rgb(rgb).map(ColorSpec::Rgb)
+        } else if let Some(hash) = spec.strip_prefix('#') {
+            parse_hash(hash).map(ColorSpec::Hash)
+        } else {
+            None
+        };
+        parsed.unwrap_or(ColorSpec::Other(spec))
+    }
+
+    ///The color, unless this is a query or a format that isn't understood.
+    pub fn rgb(&self) -> Option<RgbColor> {
+        match self {
+            ColorSpec::Rgb(color) | ColorSpec::Hash(color) => Some(*color),
+            _ => None,
+        }
+    }
+}
+
+fn parse_rgb(rgb: &str) -> Option<RgbColor> {
+    let mut channels = rgb.split('/');
+    let red = Channel::parse(channels.next()?)?;
+    let green = Channel::parse(channels.next()?)?;
+    let blue = Channel::parse(channels.next()?)?;
+    match channels.next() {
+        Some(_) => None,
+        None => Some(RgbColor { red, green, blue }),
+    }
+}
+
+fn parse_hash(hash: &str) -> Option<RgbColor> {
+    if !matches!(hash.len(), 3 | 6 | 9 | 12) || !hash.is_ascii() {
+        return None;
+    }
+    let len = hash.len() / 3;
+    Some(RgbColor {
+        red: Channel::parse(&hash[..len])?,
+        green: Channel::parse(&hash[len..2 * len])?,
+        blue: Channel::parse(&hash[2 * len..])?,
+    })
+}
+
+impl<'a> From<RgbColor> for ColorSpec<'a> {
+    fn from(color: RgbColor) -> Self {
+        ColorSpec::Rgb(color)
+    }
+}
+
+impl<'a> Display for ColorSpec<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        match self {
+            ColorSpec::Query => write!(formatter, "?"),
+            ColorSpec::Rgb(color) => {
+                write!(
+                    formatter,
+                    "rgb:{}/{}/{}",
+                    color.red, color.green, color.blue
+                )
+            }
+            ColorSpec::Hash(color) => {
+                write!(formatter, "#{}{}{}", color.red, color.green, color.blue)
+            }
+            ColorSpec::Other(spec) => write!(formatter, "{}", spec),
+        }
+    }
+}
+
+fn palette_index(s: &str) -> Option<u8> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+///The `;` separated `index;spec` pairs of an OSC 4, such as `0;?;1;rgb:ff/00/00`. There is
+///at least one pair.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PaletteColors<'a>(&'a str);
+
+impl<'a> PaletteColors<'a> {
+    ///Checks that `data` is made up of pairs of a palette entry, from 0 to 255, and a color
+    ///spec.
+    pub fn new(data: &'a str) -> Option<PaletteColors<'a>> {
+        let mut params = data.split(';');
+        while let Some(idx) = params.next() {
+            palette_index(idx)?;
+            params.next()?;
+        }
+        Some(PaletteColors(data))
+    }
+
+    ///The pairs as they were written.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    ///The palette entries, along with their colors.
+    pub fn entries(&self) -> impl Iterator<Item = (u8, ColorSpec<'a>)> + 'a {
+        let mut params = self.0.split(';');
+        core::iter::from_fn(move || {
+            let idx = palette_index(params.next()?).unwrap_or(0);
+            Some((idx, ColorSpec::parse(params.next().unwrap_or(""))))
+        })
+    }
+}
+
+impl<'a> Display for PaletteColors<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+///The `;` separated palette entries of an OSC 104. Empty when the whole palette is reset.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PaletteIndices<'a>(&'a str);
+
+impl<'a> PaletteIndices<'a> {
+    ///Checks that `data` is either empty or made up of palette entries, from 0 to 255.
+    pub fn new(data: &'a str) -> Option<PaletteIndices<'a>> {
+        if data.is_empty() || data.split(';').all(|idx| palette_index(idx).is_some()) {
+            Some(PaletteIndices(data))
+        } else {
+            None
+        }
+    }
+
+    ///The entries as they were written.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    ///Whether this resets the whole palette.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    ///The palette entries to reset.
+    pub fn entries(&self) -> impl Iterator<Item = u8> + 'a {
+        self.0.split(';').filter_map(palette_index)
+    }
+}
+
+impl<'a> Display for PaletteIndices<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{}", self.0)
+    }
+}
+
+///The colors that are set through OSC 10, 11 and 12, and reset through OSC 110, 111 and 112.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DynamicColor {
+    Foreground,
+    Background,
+    Cursor,
+}
+
+impl DynamicColor {
+    ///The OSC number that sets this color. The one resetting it is 100 more.
+    pub fn code(self) -> u8 {
+        match self {
+            DynamicColor::Foreground => 10,
+            DynamicColor::Background => 11,
+            DynamicColor::Cursor => 12,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::*;
+
+#[test]
+fn test_color_spec() {
+    assert_eq!(ColorSpec::parse("?"), ColorSpec::Query);
+    assert_eq!(
+        ColorSpec::parse("rgb:ffff/0000/8080"),
+        ColorSpec::Rgb(RgbColor::new(255, 0, 128))
+    );
+    assert_eq!(ColorSpec::parse("red"), ColorSpec::Other("red"));
+    assert_eq!(ColorSpec::parse("rgb:ff/00"), ColorSpec::Other("rgb:ff/00"));
+    assert_eq!(
+        ColorSpec::parse("rgb:ff/00/00/00"),
+        ColorSpec::Other("rgb:ff/00/00/00")
+    );
+    assert_eq!(
+        ColorSpec::parse("rgb:fffff/0/0"),
+        ColorSpec::Other("rgb:fffff/0/0")
+    );
+    assert_eq!(ColorSpec::parse("#12345"), ColorSpec::Other("#12345"));
+    assert_eq!(
+        ColorSpec::parse("rgbi:1/0/0.5"),
+        ColorSpec::Other("rgbi:1/0/0.5")
+    );
+}
+
+#[test]
+fn test_color_spec_scaling() {
+    let specs = [
+        "rgb:f/00/aaa",
+        "rgb:ffff/0/aa",
+        "#f0a",
+        "#ff00aa",
+        "#fff000aaa",
+        "#ffff0000aaaa",
+    ];
+    for spec in specs.iter() {
+        let parsed = ColorSpec::parse(spec);
+        assert_eq!(parsed.rgb().unwrap().to_rgb8(), (255, 0, 170));
+        assert_eq!(format!("{}", parsed), *spec);
+    }
+
+    assert_eq!(
+        ColorSpec::parse("rgb:8/0/0").rgb().unwrap().red.to_u16(),
+        0x8888
+    );
+    assert_eq!(
+        format!("{}", ColorSpec::from(RgbColor::new(1, 2, 255))),
+        "rgb:0101/0202/ffff"
+    );
+}
+
+#[test]
+fn test_channel_digits() {
+    assert_eq!(Channel::with_digits(0xa, 1).unwrap().to_u8(), 0xaa);
+    assert_eq!(Channel::with_digits(0xfff, 3).unwrap().to_u16(), 0xffff);
+    assert_eq!(Channel::with_digits(0, 0), None);
+    assert_eq!(Channel::with_digits(0, 5), None);
+    assert_eq!(Channel::with_digits(0x100, 2), None);
+}
+
+#[test]
+fn test_palette_lists() {
+    assert!(PaletteColors::new("0;?;255;red").is_some());
+    assert_eq!(PaletteColors::new(""), None);
+    assert_eq!(PaletteColors::new("1;?;2"), None);
+    assert_eq!(PaletteColors::new("256;?"), None);
+    assert_eq!(PaletteColors::new("+1;?"), None);
+
+    assert!(PaletteIndices::new("").unwrap().is_empty());
+    assert_eq!(PaletteIndices::new("").unwrap().entries().count(), 0);
+    assert_eq!(PaletteIndices::new("1;x"), None);
+    assert_eq!(PaletteIndices::new("1;"), None);
+}
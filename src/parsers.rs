@@ -1,16 +1,25 @@
 #[cfg(test)]
 mod tests;
 
-use crate::AnsiSequence;
+use crate::{
+    clipboard::{is_clipboard_data, is_selection},
+    AnsiSequence, ByteOutput, Clipboard, ColorSpec, ControlString, Csi, Dcs, DeviceAttribute,
+    DynamicColor, EraseMode, Esc, FileUrl, Hyperlink, ITermCommand, KittyGraphics, MarkKind,
+    ModeState, PaletteColors, PaletteIndices, Sequence, SgrParam, ShellMark, Termcaps, Terminator,
+    VsCodeMark,
+};
 
 use core::convert::TryInto;
-use heapless::Vec;
+use heapless::{
+    consts::{U16, U3, U32, U64},
+    Vec,
+};
 use nom::*;
 
 macro_rules! tag_parser {
     ($sig:ident, $tag:expr, $ret:expr) => {
         named!(
-            $sig<&str, AnsiSequence>,
+            $sig<&str, AnsiSequence<'_>>,
             do_parse!(
                 tag!($tag) >>
                 ($ret)
@@ -19,6 +28,23 @@ macro_rules! tag_parser {
     }
 }
 
+// A sequence with a single parameter that defaults to 1, such as a count of lines.
+macro_rules! def_int_parser {
+    ($sig:ident, $final:expr, $ret:path) => {
+        named!(
+            $sig<&str, AnsiSequence<'_>>,
+            do_parse!(
+                am: parse_def_cursor_int >>
+                tag!($final)             >>
+                ($ret(am))
+            )
+        );
+    }
+}
+
+// Numbers too large for their type, and lists longer than the `Vec` they go into, are rejected
+// throughout, which leaves the sequence uninterpreted. They are never taken as the default or
+// truncated.
 named!(
     parse_int<&str, u32>,
     map_res!(
@@ -27,20 +53,18 @@ named!(
     )
 );
 
-// TODO kind of ugly, would prefer to pass in the default so we could use it for
-// all escapes with defaults (not just those that default to 1).
+// A count that defaults to 1 when it is left out.
 named!(
     parse_def_cursor_int<&str, u32>,
-    map!(
+    map_res!(
         nom::digit0,
-        |s: &str| s.parse::<u32>().unwrap_or(1)
+        |s: &str| if s.is_empty() { Ok(1) } else { s.parse::<u32>() }
     )
 );
 
 named!(
-    cursor_pos<&str, AnsiSequence>,
+    cursor_pos<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")               >>
         x: parse_def_cursor_int >>
         opt!(tag!(";"))         >>
         y: parse_def_cursor_int >>
@@ -53,221 +77,478 @@ named!(
 );
 
 named!(
-    escape<&str, AnsiSequence>,
+    escape<&str, AnsiSequence<'_>>,
     do_parse!(
         tag!("\u{1b}") >>
         (AnsiSequence::Escape)
     )
 );
 
+def_int_parser!(cursor_up, "A", AnsiSequence::CursorUp);
+def_int_parser!(cursor_down, "B", AnsiSequence::CursorDown);
+def_int_parser!(cursor_forward, "C", AnsiSequence::CursorForward);
+def_int_parser!(cursor_backward, "D", AnsiSequence::CursorBackward);
+
 named!(
-    cursor_up<&str, AnsiSequence>,
+    graphics_param<&str, Option<u8>>,
+    map_res!(
+        nom::digit0,
+        |s: &str| if s.is_empty() { Ok(None) } else { s.parse::<u8>().map(Some) }
+    )
+);
+
+fn graphics_params(input: &str) -> IResult<&str, Vec<SgrParam, U64>> {
+    let mut params = Vec::new();
+    let mut input = input;
+    let mut sub = false;
+
+    loop {
+        let (rest, val) = graphics_param(input)?;
+        let param = if sub {
+            SgrParam::SubParam(val)
+        } else {
+            SgrParam::Param(val)
+        };
+        if params.push(param).is_err() {
+            return Err(Err::Error(error_position!(input, ErrorKind::TooLarge)));
+        }
+
+        match alt!(rest, tag!(";") | tag!(":")) {
+            Ok((rest, sep)) => {
+                sub = sep == ":";
+                input = rest;
+            }
+            Err(Err::Error(_)) => return Ok((rest, params)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+named!(
+    graphics_mode_empty<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")                >>
-        am: parse_def_cursor_int >>
-        tag!("A")                >>
-        (AnsiSequence::CursorUp(am))
+        tag!("m") >>
+        (AnsiSequence::SetGraphicsMode(Vec::new()))
     )
 );
 
 named!(
-    cursor_down<&str, AnsiSequence>,
+    graphics_mode_params<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")                >>
-        am: parse_def_cursor_int >>
-        tag!("B")                >>
-        (AnsiSequence::CursorDown(am))
+        params: graphics_params  >>
+        tag!("m")                 >>
+        (AnsiSequence::SetGraphicsMode(params))
+    )
+);
+
+named!(
+    graphics_mode<&str, AnsiSequence<'_>>,
+    alt!(
+          graphics_mode_empty
+        | graphics_mode_params
     )
 );
 
 named!(
-    cursor_forward<&str, AnsiSequence>,
+    set_mode<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")                >>
-        am: parse_def_cursor_int >>
-        tag!("C")                >>
-        (AnsiSequence::CursorForward(am))
+        tag!("=")                       >>
+        mode: parse_int                  >>
+        conv: expr_res!(mode.try_into()) >>
+        tag!("h")                        >>
+        (AnsiSequence::SetMode(conv))
     )
 );
 
 named!(
-    cursor_backward<&str, AnsiSequence>,
+    reset_mode<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")                >>
-        am: parse_def_cursor_int >>
-        tag!("D")                >>
-        (AnsiSequence::CursorBackward(am))
+        tag!("=")                       >>
+        mode: parse_int                  >>
+        conv: expr_res!(mode.try_into()) >>
+        tag!("l")                        >>
+        (AnsiSequence::ResetMode(conv))
     )
 );
 
 named!(
-    graphics_mode1<&str, AnsiSequence>,
+    set_top_and_bottom<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")       >>
-        val: parse_int >>
-        tag!("m")      >>
-        val: expr_res!(val.try_into()) >>
-        conv: expr_res!(Vec::from_slice(&[val])) >>
-        (AnsiSequence::SetGraphicsMode(conv))
+        x: parse_int >>
+        tag!(";")    >>
+        y: parse_int >>
+        tag!("r")    >>
+        (AnsiSequence::SetTopAndBottom(x, y))
     )
 );
 
+tag_parser!(cursor_save, "s", AnsiSequence::CursorSave);
+tag_parser!(cursor_restore, "u", AnsiSequence::CursorRestore);
+
+named!(
+    erase_mode<&str, EraseMode>,
+    map_opt!(
+        nom::digit0,
+        |s: &str| {
+            let mode = if s.is_empty() { Some(0) } else { s.parse::<u32>().ok() };
+            match mode? {
+                0 => Some(EraseMode::ToEnd),
+                1 => Some(EraseMode::ToStart),
+                2 => Some(EraseMode::All),
+                3 => Some(EraseMode::Scrollback),
+                _ => None,
+            }
+        }
+    )
+);
+
+// Only the display has a scrollback to erase.
+named!(
+    erase_line_mode<&str, EraseMode>,
+    verify!(erase_mode, |mode| mode != EraseMode::Scrollback)
+);
+
 named!(
-    graphics_mode2<&str, AnsiSequence>,
+    erase_in_display<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")       >>
-        val1: parse_int >>
-        tag!(";")       >>
-        val2: parse_int >>
-        tag!("m")       >>
-        val1: expr_res!(val1.try_into()) >>
-        val2: expr_res!(val2.try_into()) >>
-        conv: expr_res!(Vec::from_slice(&[
-            val1,
-            val2,
-        ])) >>
-        (AnsiSequence::SetGraphicsMode(conv))
+        mode: erase_mode >>
+        tag!("J")        >>
+        (AnsiSequence::EraseInDisplay(mode))
     )
 );
 
 named!(
-    graphics_mode3<&str, AnsiSequence>,
+    erase_in_line<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")       >>
-        val1: parse_int >>
-        tag!(";")       >>
-        val2: parse_int >>
-        tag!(";")       >>
-        val3: parse_int >>
-        tag!("m")       >>
-        val1: expr_res!(val1.try_into()) >>
-        val2: expr_res!(val2.try_into()) >>
-        val3: expr_res!(val3.try_into()) >>
-        conv: expr_res!(Vec::from_slice(&[
-            val1,
-            val2,
-            val3,
-        ])) >>
-        (AnsiSequence::SetGraphicsMode(conv))
+        mode: erase_line_mode >>
+        tag!("K")             >>
+        (AnsiSequence::EraseInLine(mode))
     )
 );
 
 named!(
-    graphics_mode4<&str, AnsiSequence>,
+    selective_erase_in_display<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[m") >>
-        (AnsiSequence::SetGraphicsMode(Vec::new()))
+        tag!("?")        >>
+        mode: erase_mode >>
+        tag!("J")        >>
+        (AnsiSequence::SelectiveEraseInDisplay(mode))
     )
 );
 
 named!(
-    graphics_mode5<&str, AnsiSequence>,
+    selective_erase_in_line<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")       >>
-        val1: parse_int >>
-        tag!(";")       >>
-        val2: parse_int >>
-        tag!(";")       >>
-        val3: parse_int >>
-        tag!(";")       >>
-        val4: parse_int >>
-        tag!(";")       >>
-        val5: parse_int >>
-        tag!("m")       >>
-        val1: expr_res!(val1.try_into()) >>
-        val2: expr_res!(val2.try_into()) >>
-        val3: expr_res!(val3.try_into()) >>
-        val4: expr_res!(val4.try_into()) >>
-        val5: expr_res!(val5.try_into()) >>
-        conv: expr_res!(Vec::from_slice(&[
-            val1,
-            val2,
-            val3,
-            val4,
-            val5,
-        ])) >>
-        (AnsiSequence::SetGraphicsMode(conv))
-    )
-);
-
-named!(
-    graphics_mode<&str, AnsiSequence>,
+        tag!("?")             >>
+        mode: erase_line_mode >>
+        tag!("K")             >>
+        (AnsiSequence::SelectiveEraseInLine(mode))
+    )
+);
+
+def_int_parser!(cursor_next_line, "E", AnsiSequence::CursorNextLine);
+def_int_parser!(cursor_prev_line, "F", AnsiSequence::CursorPreviousLine);
+def_int_parser!(cursor_column, "G", AnsiSequence::CursorColumn);
+def_int_parser!(
+    char_pos_absolute,
+    "`",
+    AnsiSequence::CharacterPositionAbsolute
+);
+def_int_parser!(line_pos_absolute, "d", AnsiSequence::LinePositionAbsolute);
+def_int_parser!(
+    char_pos_relative,
+    "a",
+    AnsiSequence::CharacterPositionRelative
+);
+def_int_parser!(line_pos_relative, "e", AnsiSequence::LinePositionRelative);
+def_int_parser!(cursor_forward_tab, "I", AnsiSequence::CursorForwardTab);
+def_int_parser!(cursor_backward_tab, "Z", AnsiSequence::CursorBackwardTab);
+def_int_parser!(erase_characters, "X", AnsiSequence::EraseCharacters);
+
+// The `;` separated modes of a set or reset. At least one mode has to be given.
+fn mode_list<T: From<u16>>(input: &str) -> IResult<&str, Vec<T, U16>> {
+    let mut modes = Vec::new();
+    let mut input = input;
+
+    loop {
+        let (rest, mode) = map_res!(input, nom::digit, |s: &str| s.parse::<u16>())?;
+        if modes.push(T::from(mode)).is_err() {
+            return Err(Err::Error(error_position!(input, ErrorKind::TooLarge)));
+        }
+
+        match tag!(rest, ";") {
+            Ok((rest, _)) => input = rest,
+            Err(Err::Error(_)) => return Ok((rest, modes)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+named!(
+    mode_set<&str, bool>,
     alt!(
-          graphics_mode1
-        | graphics_mode2
-        | graphics_mode3
-        | graphics_mode4
-        | graphics_mode5
+          value!(true, tag!("h"))
+        | value!(false, tag!("l"))
     )
 );
 
 named!(
-    set_mode<&str, AnsiSequence>,
+    dec_private_mode<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[=")                       >>
-        mode: parse_int                  >>
-        conv: expr_res!(mode.try_into()) >>
-        tag!("h")                        >>
-        (AnsiSequence::SetMode(conv))
+        tag!("?")         >>
+        modes: mode_list  >>
+        set: mode_set     >>
+        (AnsiSequence::DecPrivateMode(modes, set))
     )
 );
 
 named!(
-    reset_mode<&str, AnsiSequence>,
+    ansi_mode<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[=")                       >>
-        mode: parse_int                  >>
-        conv: expr_res!(mode.try_into()) >>
-        tag!("l")                        >>
-        (AnsiSequence::ResetMode(conv))
+        modes: mode_list  >>
+        set: mode_set     >>
+        (AnsiSequence::AnsiMode(modes, set))
     )
 );
 
 named!(
-    set_top_and_bottom<&str, AnsiSequence>,
+    mode_code<&str, u16>,
+    map_res!(
+        nom::digit,
+        |s: &str| s.parse::<u16>()
+    )
+);
+
+named!(
+    mode_state<&str, ModeState>,
+    map_opt!(
+        nom::digit,
+        |s: &str| s.parse::<u8>().ok().and_then(ModeState::from_code)
+    )
+);
+
+named!(
+    request_dec_mode<&str, AnsiSequence<'_>>,
     do_parse!(
-        tag!("[")    >>
-        x: parse_int >>
-        tag!(";")    >>
-        y: parse_int >>
-        tag!("r")    >>
-        (AnsiSequence::SetTopAndBottom(x, y))
+        tag!("?")        >>
+        mode: mode_code  >>
+        tag!("$p")       >>
+        (AnsiSequence::RequestDecMode(mode.into()))
     )
 );
 
-tag_parser!(cursor_save, "[s", AnsiSequence::CursorSave);
-tag_parser!(cursor_restore, "[u", AnsiSequence::CursorRestore);
-tag_parser!(erase_display, "[2J", AnsiSequence::EraseDisplay);
+named!(
+    request_ansi_mode<&str, AnsiSequence<'_>>,
+    do_parse!(
+        mode: mode_code  >>
+        tag!("$p")       >>
+        (AnsiSequence::RequestAnsiMode(mode.into()))
+    )
+);
+
+named!(
+    dec_mode_report<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!("?")          >>
+        mode: mode_code    >>
+        tag!(";")          >>
+        state: mode_state  >>
+        tag!("$y")         >>
+        (AnsiSequence::DecModeReport(mode.into(), state))
+    )
+);
+
+named!(
+    ansi_mode_report<&str, AnsiSequence<'_>>,
+    do_parse!(
+        mode: mode_code    >>
+        tag!(";")          >>
+        state: mode_state  >>
+        tag!("$y")         >>
+        (AnsiSequence::AnsiModeReport(mode.into(), state))
+    )
+);
+
+// The `;` separated codes of a DA1 reply: the device class, then the features.
+fn attribute_list(input: &str) -> IResult<&str, (u16, Vec<DeviceAttribute, U32>)> {
+    let (mut input, class) = map_res!(input, nom::digit, |s: &str| s.parse::<u16>())?;
+    let mut features = Vec::new();
+
+    loop {
+        match tag!(input, ";") {
+            Ok((rest, _)) => input = rest,
+            Err(Err::Error(_)) => return Ok((input, (class, features))),
+            Err(e) => return Err(e),
+        }
+
+        let (rest, feature) = map_res!(input, nom::digit0, |s: &str| if s.is_empty() {
+            Ok(DeviceAttribute::Empty)
+        } else {
+            s.parse::<u16>().map(DeviceAttribute::from)
+        })?;
+        if features.push(feature).is_err() {
+            return Err(Err::Error(error_position!(input, ErrorKind::TooLarge)));
+        }
+        input = rest;
+    }
+}
+
+named!(
+    request_primary_attributes<&str, AnsiSequence<'_>>,
+    do_parse!(
+        opt!(tag!("0")) >>
+        tag!("c")       >>
+        (AnsiSequence::RequestPrimaryAttributes)
+    )
+);
+
+named!(
+    primary_attributes_report<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!("?")              >>
+        codes: attribute_list  >>
+        tag!("c")              >>
+        (AnsiSequence::PrimaryAttributes(codes.0, codes.1))
+    )
+);
+
+named!(
+    request_secondary_attributes<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!(">")       >>
+        opt!(tag!("0")) >>
+        tag!("c")       >>
+        (AnsiSequence::RequestSecondaryAttributes)
+    )
+);
+
+named!(
+    secondary_attributes_report<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!(">")          >>
+        kind: parse_int    >>
+        tag!(";")          >>
+        version: parse_int >>
+        tag!(";")          >>
+        rom: parse_int     >>
+        tag!("c")          >>
+        (AnsiSequence::SecondaryAttributes(kind, version, rom))
+    )
+);
+
+named!(
+    request_tertiary_attributes<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!("=")       >>
+        opt!(tag!("0")) >>
+        tag!("c")       >>
+        (AnsiSequence::RequestTertiaryAttributes)
+    )
+);
+
+named!(
+    request_terminal_version<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!(">")       >>
+        opt!(tag!("0")) >>
+        tag!("q")       >>
+        (AnsiSequence::RequestTerminalVersion)
+    )
+);
+
+tag_parser!(
+    request_device_status,
+    "5n",
+    AnsiSequence::RequestDeviceStatus
+);
 tag_parser!(
-    erase_display_from_cursor,
-    "[J",
-    AnsiSequence::EraseDisplayFromCursor
-);
-tag_parser!(erase_line, "[K", AnsiSequence::EraseLine);
-tag_parser!(hide_cursor, "[?25l", AnsiSequence::HideCursor);
-tag_parser!(show_cursor, "[?25h", AnsiSequence::ShowCursor);
-tag_parser!(cursor_to_app, "[?1h", AnsiSequence::CursorToApp);
-tag_parser!(set_new_line_mode, "[20h", AnsiSequence::SetNewLineMode);
-tag_parser!(set_col_132, "[?3h", AnsiSequence::SetCol132);
-tag_parser!(set_smooth_scroll, "[?4h", AnsiSequence::SetSmoothScroll);
-tag_parser!(set_reverse_video, "[?5h", AnsiSequence::SetReverseVideo);
-tag_parser!(set_origin_rel, "[?6h", AnsiSequence::SetOriginRelative);
-tag_parser!(set_auto_wrap, "[?7h", AnsiSequence::SetAutoWrap);
-tag_parser!(set_auto_repeat, "[?8h", AnsiSequence::SetAutoRepeat);
-tag_parser!(set_interlacing, "[?9h", AnsiSequence::SetInterlacing);
-tag_parser!(set_linefeed, "[20l", AnsiSequence::SetLineFeedMode);
-tag_parser!(set_cursorkey, "[?1l", AnsiSequence::SetCursorKeyToCursor);
-tag_parser!(set_vt52, "[?2l", AnsiSequence::SetVT52);
-tag_parser!(set_col80, "[?3l", AnsiSequence::SetCol80);
-tag_parser!(set_jump_scroll, "[?4l", AnsiSequence::SetJumpScrolling);
-tag_parser!(set_normal_video, "[?5l", AnsiSequence::SetNormalVideo);
-tag_parser!(set_origin_abs, "[?6l", AnsiSequence::SetOriginAbsolute);
-tag_parser!(reset_auto_wrap, "[?7l", AnsiSequence::ResetAutoWrap);
-tag_parser!(reset_auto_repeat, "[?8l", AnsiSequence::ResetAutoRepeat);
-tag_parser!(reset_interlacing, "[?9l", AnsiSequence::ResetInterlacing);
+    device_status_ok,
+    "0n",
+    AnsiSequence::DeviceStatusReport(true)
+);
+tag_parser!(
+    device_status_error,
+    "3n",
+    AnsiSequence::DeviceStatusReport(false)
+);
+tag_parser!(
+    request_cursor_position,
+    "6n",
+    AnsiSequence::RequestCursorPosition
+);
+
+named!(
+    cursor_position_report<&str, AnsiSequence<'_>>,
+    do_parse!(
+        line: parse_int >>
+        tag!(";")       >>
+        col: parse_int  >>
+        tag!("R")       >>
+        (AnsiSequence::CursorPositionReport(line, col))
+    )
+);
+
+// Device attributes and status, in both directions.
+named!(
+    csi_device<&str, AnsiSequence<'_>>,
+    alt!(
+          request_primary_attributes
+        | primary_attributes_report
+        | request_secondary_attributes
+        | secondary_attributes_report
+        | request_tertiary_attributes
+        | request_terminal_version
+        | request_device_status
+        | device_status_ok
+        | device_status_error
+        | request_cursor_position
+        | cursor_position_report
+    )
+);
+
+def_int_parser!(insert_characters, "@", AnsiSequence::InsertCharacters);
+def_int_parser!(delete_characters, "P", AnsiSequence::DeleteCharacters);
+def_int_parser!(insert_lines, "L", AnsiSequence::InsertLines);
+def_int_parser!(delete_lines, "M", AnsiSequence::DeleteLines);
+def_int_parser!(scroll_up, "S", AnsiSequence::ScrollUp);
+def_int_parser!(scroll_down, "T", AnsiSequence::ScrollDown);
+def_int_parser!(repeat_character, "b", AnsiSequence::RepeatCharacter);
+tag_parser!(hide_cursor, "?25l", AnsiSequence::HideCursor);
+tag_parser!(show_cursor, "?25h", AnsiSequence::ShowCursor);
+tag_parser!(cursor_to_app, "?1h", AnsiSequence::CursorToApp);
+tag_parser!(set_new_line_mode, "20h", AnsiSequence::SetNewLineMode);
+tag_parser!(set_col_132, "?3h", AnsiSequence::SetCol132);
+tag_parser!(set_smooth_scroll, "?4h", AnsiSequence::SetSmoothScroll);
+tag_parser!(set_reverse_video, "?5h", AnsiSequence::SetReverseVideo);
+tag_parser!(set_origin_rel, "?6h", AnsiSequence::SetOriginRelative);
+tag_parser!(set_auto_wrap, "?7h", AnsiSequence::SetAutoWrap);
+tag_parser!(set_auto_repeat, "?8h", AnsiSequence::SetAutoRepeat);
+tag_parser!(set_interlacing, "?9h", AnsiSequence::SetInterlacing);
+tag_parser!(set_linefeed, "20l", AnsiSequence::SetLineFeedMode);
+tag_parser!(set_cursorkey, "?1l", AnsiSequence::SetCursorKeyToCursor);
+tag_parser!(set_vt52, "?2l", AnsiSequence::SetVT52);
+tag_parser!(set_col80, "?3l", AnsiSequence::SetCol80);
+tag_parser!(set_jump_scroll, "?4l", AnsiSequence::SetJumpScrolling);
+tag_parser!(set_normal_video, "?5l", AnsiSequence::SetNormalVideo);
+tag_parser!(set_origin_abs, "?6l", AnsiSequence::SetOriginAbsolute);
+tag_parser!(reset_auto_wrap, "?7l", AnsiSequence::ResetAutoWrap);
+tag_parser!(reset_auto_repeat, "?8l", AnsiSequence::ResetAutoRepeat);
+tag_parser!(reset_interlacing, "?9l", AnsiSequence::ResetInterlacing);
 
 tag_parser!(set_alternate_keypad, "=", AnsiSequence::SetAlternateKeypad);
 tag_parser!(set_numeric_keypad, ">", AnsiSequence::SetNumericKeypad);
+tag_parser!(dec_save_cursor, "7", AnsiSequence::DecSaveCursor);
+tag_parser!(dec_restore_cursor, "8", AnsiSequence::DecRestoreCursor);
+tag_parser!(full_reset, "c", AnsiSequence::FullReset);
+tag_parser!(index, "D", AnsiSequence::Index);
+tag_parser!(next_line, "E", AnsiSequence::NextLine);
+tag_parser!(reverse_index, "M", AnsiSequence::ReverseIndex);
+tag_parser!(tab_set, "H", AnsiSequence::TabSet);
+tag_parser!(
+    screen_alignment_test,
+    "#8",
+    AnsiSequence::ScreenAlignmentTest
+);
 tag_parser!(set_uk_g0, "(A", AnsiSequence::SetUKG0);
 tag_parser!(set_uk_g1, ")A", AnsiSequence::SetUKG1);
 tag_parser!(set_us_g0, "(B", AnsiSequence::SetUSG0);
@@ -281,20 +562,246 @@ tag_parser!(set_g1_graph, ")2", AnsiSequence::SetG1AltAndSpecialGraph);
 tag_parser!(set_single_shift2, "N", AnsiSequence::SetSingleShift2);
 tag_parser!(set_single_shift3, "O", AnsiSequence::SetSingleShift3);
 
+named_args!(
+    osc_title<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        ps: one_of!("012")  >>
+        tag!(";")           >>
+        title: rest_s       >>
+        (match ps {
+            '0' => AnsiSequence::SetIconNameAndWindowTitle(title, term),
+            '1' => AnsiSequence::SetIconName(title, term),
+            _ => AnsiSequence::SetWindowTitle(title, term),
+        })
+    )
+);
+
+named_args!(
+    osc_hyperlink<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("8;")                        >>
+        params: take_till!(|c| c == ';') >>
+        tag!(";")                         >>
+        uri: rest_s                       >>
+        (AnsiSequence::Hyperlink(Hyperlink { params, uri }, term))
+    )
+);
+
+// What follows `104`, which is either nothing or the `;` separated entries to reset.
+fn reset_palette_indices(s: &str) -> Option<PaletteIndices<'_>> {
+    match s {
+        "" => PaletteIndices::new(s),
+        _ => PaletteIndices::new(s.strip_prefix(';')?).filter(|indices| !indices.is_empty()),
+    }
+}
+
+// The specs of an OSC 10, 11 or 12, each one for the color after the one before it. Colors
+// past the cursor color aren't known, so a spec for one of them is rejected.
+fn dynamic_color_specs(which: DynamicColor, s: &str) -> Option<Vec<ColorSpec<'_>, U3>> {
+    let mut specs = Vec::new();
+    for spec in s.split(';') {
+        if specs.len() + usize::from(which.code()) > 12 {
+            return None;
+        }
+        //Can't fail, there is room for the three colors.
+        let _ = specs.push(ColorSpec::parse(spec));
+    }
+    Some(specs)
+}
+
+named_args!(
+    osc_palette<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("4;")                                   >>
+        colors: map_opt!(rest_s, PaletteColors::new) >>
+        (AnsiSequence::PaletteColor(colors, term))
+    )
+);
+
+named_args!(
+    osc_reset_palette<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("104")                                      >>
+        indices: map_opt!(rest_s, reset_palette_indices) >>
+        (AnsiSequence::ResetPaletteColor(indices, term))
+    )
+);
+
+named_args!(
+    osc_dynamic_color<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("1")                                                  >>
+        which: map!(one_of!("012"), dynamic_color)                 >>
+        tag!(";")                                                  >>
+        specs: map_opt!(rest_s, |s| dynamic_color_specs(which, s)) >>
+        (AnsiSequence::DynamicColor(which, specs, term))
+    )
+);
+
+named_args!(
+    osc_reset_dynamic_color<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("11")         >>
+        ps: one_of!("012") >>
+        (AnsiSequence::ResetDynamicColor(dynamic_color(ps), term))
+    )
+);
+
+named_args!(
+    osc_clipboard<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("52;")                              >>
+        selection: take_while!(is_selection)     >>
+        tag!(";")                                >>
+        data: verify!(rest_s, is_clipboard_data) >>
+        (AnsiSequence::Clipboard(Clipboard { selection, data }, term))
+    )
+);
+
+// What follows a semantic prompt mark, which is either nothing or `;` and its parameters.
+fn mark_params(s: &str) -> Option<Option<&str>> {
+    match s {
+        "" => Some(None),
+        _ if s.starts_with(';') => Some(Some(&s[1..])),
+        _ => None,
+    }
+}
+
+named!(
+    shell_mark<&str, ShellMark<'_>>,
+    do_parse!(
+        kind: one_of!("ABCD")                  >>
+        params: map_opt!(rest_s, mark_params) >>
+        (ShellMark { kind: MarkKind::from_char(kind), params })
+    )
+);
+
+named_args!(
+    osc_semantic_prompt<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("133;")     >>
+        mark: shell_mark >>
+        (AnsiSequence::SemanticPrompt(mark, term))
+    )
+);
+
 named!(
-    combined<&str, AnsiSequence>,
+    vscode_mark<&str, VsCodeMark<'_>>,
     alt!(
-          escape
-        | cursor_pos
+          map!(shell_mark, VsCodeMark::Mark)
+        | do_parse!(
+            tag!("E;")  >>
+            cmd: rest_s >>
+            (VsCodeMark::CommandLine(cmd))
+        )
+        | do_parse!(
+            tag!("P;")                      >>
+            key: take_till!(|c| c == '=') >>
+            tag!("=")                       >>
+            val: rest_s                     >>
+            (VsCodeMark::Property(key, val))
+        )
+    )
+);
+
+named_args!(
+    osc_vscode<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("633;")      >>
+        mark: vscode_mark >>
+        (AnsiSequence::VsCodeShellIntegration(mark, term))
+    )
+);
+
+named_args!(
+    osc_working_directory<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("7;file://")              >>
+        host: take_till!(|c| c == '/') >>
+        path: rest_s                   >>
+        (AnsiSequence::WorkingDirectory(FileUrl { host, path }, term))
+    )
+);
+
+named_args!(
+    osc_current_dir<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("1337;CurrentDir=") >>
+        dir: rest_s              >>
+        (AnsiSequence::CurrentDir(dir, term))
+    )
+);
+
+named_args!(
+    osc_iterm<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    do_parse!(
+        tag!("1337;")                           >>
+        cmd: map_opt!(rest_s, ITermCommand::parse) >>
+        (AnsiSequence::ITerm(cmd, term))
+    )
+);
+
+fn dynamic_color(ps: char) -> DynamicColor {
+    match ps {
+        '0' => DynamicColor::Foreground,
+        '1' => DynamicColor::Background,
+        _ => DynamicColor::Cursor,
+    }
+}
+
+named!(
+    csi_cursor<&str, AnsiSequence<'_>>,
+    alt!(
+          cursor_next_line
+        | cursor_prev_line
+        | cursor_column
+        | char_pos_absolute
+        | line_pos_absolute
+        | char_pos_relative
+        | line_pos_relative
+        | cursor_forward_tab
+        | cursor_backward_tab
+    )
+);
+
+named!(
+    csi_erase<&str, AnsiSequence<'_>>,
+    alt!(
+          erase_in_display
+        | erase_in_line
+        | selective_erase_in_display
+        | selective_erase_in_line
+        | erase_characters
+    )
+);
+
+named!(
+    csi_edit<&str, AnsiSequence<'_>>,
+    alt!(
+          insert_characters
+        | delete_characters
+        | insert_lines
+        | delete_lines
+        | scroll_up
+        | scroll_down
+        | repeat_character
+    )
+);
+
+// Everything following `ESC [`.
+named!(
+    csi_combined<&str, AnsiSequence<'_>>,
+    alt!(
+          cursor_pos
         | cursor_up
         | cursor_down
         | cursor_forward
         | cursor_backward
         | cursor_save
         | cursor_restore
-        | erase_display
-        | erase_display_from_cursor
-        | erase_line
+        | csi_cursor
+        | csi_erase
+        | csi_edit
         | graphics_mode
         | set_mode
         | reset_mode
@@ -320,6 +827,107 @@ named!(
         | reset_auto_repeat
         | reset_interlacing
         | set_top_and_bottom
+        // After the single mode sequences above, which keep their own variants.
+        | dec_private_mode
+        | ansi_mode
+        | request_dec_mode
+        | request_ansi_mode
+        | dec_mode_report
+        | ansi_mode_report
+        | csi_device
+    )
+);
+
+// The data of an OSC string, which comes without its terminator.
+named_args!(
+    osc_combined<'a>(term: Terminator)<&'a str, AnsiSequence<'a>>,
+    alt!(
+          call!(osc_title, term)
+        | call!(osc_hyperlink, term)
+        | call!(osc_palette, term)
+        | call!(osc_reset_palette, term)
+        // Has to come before `osc_dynamic_color`, which runs out of input on `11x`.
+        | call!(osc_reset_dynamic_color, term)
+        | call!(osc_dynamic_color, term)
+        | call!(osc_clipboard, term)
+        | call!(osc_semantic_prompt, term)
+        | call!(osc_vscode, term)
+        | call!(osc_working_directory, term)
+        | call!(osc_current_dir, term)
+        | call!(osc_iterm, term)
+    )
+);
+
+fn osc(input: &str) -> IResult<&str, AnsiSequence<'_>> {
+    let (rest, string) = preceded!(input, tag!("]"), control_string)?;
+    match osc_combined(string.data, string.terminator) {
+        Ok(("", seq)) => Ok((rest, seq)),
+        _ => Err(Err::Error(error_position!(input, ErrorKind::Alt))),
+    }
+}
+
+// Device control strings are told apart by their header alone, so it is matched on directly.
+fn is_unit_id(data: &str) -> bool {
+    !data.is_empty() && data.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn dcs_combined<'a>(dcs: &Dcs<'a>) -> Option<AnsiSequence<'a>> {
+    let term = dcs.terminator;
+    let valid = match dcs.params {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    };
+
+    match (
+        dcs.private_marker,
+        dcs.params,
+        dcs.intermediates,
+        dcs.final_byte,
+    ) {
+        (None, "", "$", 'q') => Some(AnsiSequence::RequestStatusString(dcs.data, term)),
+        (None, _, "$", 'r') => Some(AnsiSequence::StatusStringReport(valid?, dcs.data, term)),
+        (None, "", "+", 'q') => Termcaps::new(dcs.data)
+            .filter(|caps| !caps.has_values())
+            .map(|caps| AnsiSequence::RequestTermcap(caps, term)),
+        (None, _, "+", 'r') => Some(AnsiSequence::TermcapReport(
+            valid?,
+            Termcaps::new(dcs.data)?,
+            term,
+        )),
+        (None, "", "!", '|') if is_unit_id(dcs.data) => {
+            Some(AnsiSequence::TertiaryAttributes(dcs.data, term))
+        }
+        (Some('>'), "", "", '|') => Some(AnsiSequence::TerminalVersion(dcs.data, term)),
+        _ => None,
+    }
+}
+
+fn device_control(input: &str) -> IResult<&str, AnsiSequence<'_>> {
+    let (rest, dcs) = preceded!(input, tag!("P"), dcs_body)?;
+    match dcs_combined(&dcs) {
+        Some(seq) => Ok((rest, seq)),
+        None => Err(Err::Error(error_position!(input, ErrorKind::Alt))),
+    }
+}
+
+fn apc_combined<'a>(string: &ControlString<'a>) -> Option<AnsiSequence<'a>> {
+    KittyGraphics::parse(string.data).map(|cmd| AnsiSequence::KittyGraphics(cmd, string.terminator))
+}
+
+fn apc(input: &str) -> IResult<&str, AnsiSequence<'_>> {
+    let (rest, string) = preceded!(input, tag!("_"), control_string)?;
+    match apc_combined(&string) {
+        Some(seq) => Ok((rest, seq)),
+        None => Err(Err::Error(error_position!(input, ErrorKind::Alt))),
+    }
+}
+
+// Everything following a lone `ESC`.
+named!(
+    esc_combined<&str, AnsiSequence<'_>>,
+    alt!(
+          escape
         | set_alternate_keypad
         | set_numeric_keypad
         | set_uk_g0
@@ -334,14 +942,391 @@ named!(
         | set_g1_graph
         | set_single_shift2
         | set_single_shift3
+        | dec_save_cursor
+        | dec_restore_cursor
+        | full_reset
+        | index
+        | next_line
+        | reverse_index
+        | tab_set
+        | screen_alignment_test
     )
 );
 
 named!(
-    pub parse_escape<&str, AnsiSequence>,
+    pub parse_escape<&str, AnsiSequence<'_>>,
+    do_parse!(
+        tag!("\u{1b}") >>
+        seq: alt!(
+              preceded!(tag!("["), csi_combined)
+            | osc
+            | device_control
+            | apc
+            | esc_combined
+        ) >>
+        (seq)
+    )
+);
+
+// The byte classes of ECMA-48 5.4. Parameter bytes include the private markers, which are only
+// split out when they lead the parameters.
+fn is_param(c: char) -> bool {
+    ('0'..='?').contains(&c)
+}
+
+fn is_private_marker(c: char) -> bool {
+    ('<'..='?').contains(&c)
+}
+
+fn is_intermediate(c: char) -> bool {
+    (' '..='/').contains(&c)
+}
+
+fn is_final(c: char) -> bool {
+    ('@'..='~').contains(&c)
+}
+
+fn is_esc_final(c: char) -> bool {
+    ('0'..='~').contains(&c)
+}
+
+named!(
+    private_marker<&str, Option<char>>,
+    opt!(verify!(nom::anychar, is_private_marker))
+);
+
+named!(
+    terminator<&str, Terminator>,
+    alt!(
+          tag!("\u{7}")    => { |_| Terminator::Bel }
+        | tag!("\u{1b}\\") => { |_| Terminator::St }
+    )
+);
+
+named!(
+    control_string<&str, ControlString<'_>>,
+    do_parse!(
+        data: take_till!(|c| c == '\u{7}' || c == '\u{1b}') >>
+        term: terminator                                    >>
+        (ControlString { data, terminator: term })
+    )
+);
+
+named!(
+    csi_body<&str, Csi<'_>>,
+    do_parse!(
+        marker: private_marker                      >>
+        params: take_while!(is_param)               >>
+        intermediates: take_while!(is_intermediate) >>
+        final_byte: verify!(nom::anychar, is_final) >>
+        (Csi {
+            private_marker: marker,
+            params,
+            intermediates,
+            final_byte,
+        })
+    )
+);
+
+named!(
+    dcs_header<&str, (Option<char>, &str, &str, char)>,
+    do_parse!(
+        marker: private_marker                      >>
+        params: take_while!(is_param)               >>
+        intermediates: take_while!(is_intermediate) >>
+        final_byte: verify!(nom::anychar, is_final) >>
+        ((marker, params, intermediates, final_byte))
+    )
+);
+
+fn dcs<'a>(
+    (private_marker, params, intermediates, final_byte): (Option<char>, &'a str, &'a str, char),
+    string: ControlString<'a>,
+) -> Dcs<'a> {
+    Dcs {
+        private_marker,
+        params,
+        intermediates,
+        final_byte,
+        data: string.data,
+        terminator: string.terminator,
+    }
+}
+
+// Unlike other strings, a device control string can only be ended by ST. tmux wraps sequences
+// that it passes through to the outer terminal in one, with every ESC doubled.
+fn dcs_string(input: &str) -> IResult<&str, ControlString<'_>> {
+    let bytes = input.as_bytes();
+    let mut i = 0;
+    loop {
+        match bytes[i..].iter().position(|b| *b == 0x1b) {
+            Some(pos) => i += pos,
+            None => return Err(Err::Incomplete(Needed::Unknown)),
+        }
+        match bytes.get(i + 1) {
+            Some(b'\\') => {
+                let string = ControlString {
+                    data: &input[..i],
+                    terminator: Terminator::St,
+                };
+                return Ok((&input[i + 2..], string));
+            }
+            Some(0x1b) => i += 2,
+            Some(_) => return Err(Err::Error(error_position!(input, ErrorKind::Escaped))),
+            None => return Err(Err::Incomplete(Needed::Size(1))),
+        }
+    }
+}
+
+named!(
+    dcs_body<&str, Dcs<'_>>,
+    do_parse!(
+        header: dcs_header >>
+        string: dcs_string >>
+        (dcs(header, string))
+    )
+);
+
+// The introducers of control sequences and strings are valid finals too, but a malformed CSI or
+// OSC must not be mistaken for a two byte escape.
+named!(
+    esc_sequence<&str, Sequence<'_>>,
+    do_parse!(
+        not!(one_of!("[]P_^X"))                         >>
+        intermediates: take_while!(is_intermediate)     >>
+        final_byte: verify!(nom::anychar, is_esc_final) >>
+        (Sequence::Esc(Esc {
+            intermediates,
+            final_byte,
+        }))
+    )
+);
+
+named!(
+    any_sequence<&str, Sequence<'_>>,
+    alt!(
+          preceded!(tag!("["), csi_body)       => { Sequence::Csi }
+        | preceded!(tag!("P"), dcs_body)       => { Sequence::Dcs }
+        | preceded!(tag!("]"), control_string) => { Sequence::Osc }
+        | preceded!(tag!("_"), control_string) => { Sequence::Apc }
+        | preceded!(tag!("^"), control_string) => { Sequence::Pm }
+        | preceded!(tag!("X"), control_string) => { Sequence::Sos }
+        | esc_sequence
+    )
+);
+
+named!(
+    pub parse_sequence<&str, Sequence<'_>>,
     do_parse!(
         tag!("\u{1b}")    >>
-        seq: combined     >>
+        seq: any_sequence >>
         (seq)
     )
 );
+
+// Gives meaning to a sequence split off by `parse_sequence`. `body` is everything after the
+// introducer, so for a control sequence it starts after the `[`.
+pub(crate) fn interpret<'a>(seq: &Sequence<'a>, body: &'a str) -> Option<AnsiSequence<'a>> {
+    let res = match seq {
+        Sequence::Csi(_) => csi_combined(body),
+        Sequence::Esc(_) => esc_combined(body),
+        Sequence::Osc(string) => osc_combined(string.data, string.terminator),
+        Sequence::Dcs(dcs) => return dcs_combined(dcs),
+        Sequence::Apc(string) => return apc_combined(string),
+        _ => return None,
+    };
+
+    match res {
+        Ok(("", esc)) => Some(esc),
+        _ => None,
+    }
+}
+
+// The finals of the two byte escapes that 8-bit C1 controls stand in for. Interpreting a C1
+// control needs the final as text, which doesn't appear anywhere in the input.
+const C1_FINALS: &str = "@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Csi,
+    Esc,
+    Osc,
+    Dcs,
+    Apc,
+    Pm,
+    Sos,
+}
+
+fn string_kind(introducer: u8) -> Option<Kind> {
+    match introducer {
+        b']' => Some(Kind::Osc),
+        b'P' => Some(Kind::Dcs),
+        b'_' => Some(Kind::Apc),
+        b'^' => Some(Kind::Pm),
+        b'X' => Some(Kind::Sos),
+        _ => None,
+    }
+}
+
+// How many bytes the UTF-8 character starting with `byte` takes up. Anything that can't start
+// a character is a single byte.
+pub(crate) fn utf8_width(byte: u8) -> usize {
+    match byte {
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => 1,
+    }
+}
+
+// Finds the first byte in the body of a control string that `is_end` picks out. Whole UTF-8
+// characters are stepped over, so that continuation bytes such as the 0x9c in `本` aren't taken
+// for an 8-bit ST. Runs out of input at the end of the body, or in the middle of a character.
+fn string_end(body: &[u8], is_end: impl Fn(u8) -> bool) -> Option<usize> {
+    let mut i = 0;
+    while i < body.len() {
+        let width = utf8_width(body[i]);
+        if width == 1 && is_end(body[i]) {
+            return Some(i);
+        }
+
+        i += match body.get(i..i + width) {
+            Some(c) if core::str::from_utf8(c).is_ok() => width,
+            None if body[i + 1..].iter().all(|b| (0x80..=0xbf).contains(b)) => return None,
+            _ => 1,
+        };
+    }
+    None
+}
+
+// Finds where a sequence of the given kind ends, without looking at what is inside it. This
+// bounds the UTF-8 check to the sequence itself. The second value is true if the sequence was
+// ended by an 8-bit ST, which is left out of the returned length.
+fn sequence_len(kind: Kind, body: &[u8]) -> Option<(usize, bool)> {
+    match kind {
+        Kind::Csi => body
+            .iter()
+            .position(|b| !(0x20..=0x3f).contains(b))
+            .map(|pos| (pos + 1, false)),
+        Kind::Esc => body
+            .iter()
+            .position(|b| !(0x20..=0x2f).contains(b))
+            .map(|pos| (pos + 1, false)),
+        Kind::Dcs => {
+            let mut i = 0;
+            loop {
+                i += string_end(&body[i..], |b| b == 0x1b || b == 0x9c)?;
+                match (body[i], body.get(i + 1)) {
+                    (0x9c, _) => return Some((i, true)),
+                    (_, Some(0x1b)) => i += 2,
+                    (_, Some(_)) => return Some((i + 2, false)),
+                    (_, None) => return None,
+                }
+            }
+        }
+        _ => {
+            let pos = string_end(body, |b| b == 0x07 || b == 0x1b || b == 0x9c)?;
+            match body[pos] {
+                0x07 => Some((pos + 1, false)),
+                0x1b if pos + 1 < body.len() => Some((pos + 2, false)),
+                0x1b => None,
+                _ => Some((pos, true)),
+            }
+        }
+    }
+}
+
+fn parse_body(kind: Kind, body: &str, c1_st: bool) -> Option<Sequence<'_>> {
+    let st = ControlString {
+        data: body,
+        terminator: Terminator::St,
+    };
+
+    let res = match kind {
+        Kind::Csi => csi_body(body).map(|(rest, csi)| (rest, Sequence::Csi(csi))),
+        Kind::Esc => esc_sequence(body),
+        Kind::Dcs if c1_st => dcs_header(body).map(|(data, header)| {
+            let data = ControlString { data, ..st };
+            ("", Sequence::Dcs(dcs(header, data)))
+        }),
+        Kind::Dcs => dcs_body(body).map(|(rest, dcs)| (rest, Sequence::Dcs(dcs))),
+        _ => {
+            let (rest, string) = if c1_st {
+                ("", st)
+            } else {
+                control_string(body).ok()?
+            };
+            let seq = match kind {
+                Kind::Osc => Sequence::Osc(string),
+                Kind::Apc => Sequence::Apc(string),
+                Kind::Pm => Sequence::Pm(string),
+                _ => Sequence::Sos(string),
+            };
+            Ok((rest, seq))
+        }
+    };
+
+    match res {
+        Ok(("", seq)) => Some(seq),
+        _ => None,
+    }
+}
+
+// Whether `dat` starts with a sequence that needs more input to be finished.
+pub(crate) fn is_incomplete_bytes(dat: &[u8]) -> bool {
+    let (kind, start) = match dat.first() {
+        Some(0x1b) => match dat.get(1) {
+            None => return true,
+            Some(0x1b) => return false,
+            Some(b'[') => (Kind::Csi, 2),
+            Some(&b) => string_kind(b).map_or((Kind::Esc, 1), |kind| (kind, 2)),
+        },
+        Some(0x9b) => (Kind::Csi, 1),
+        Some(&c1 @ 0x80..=0x9f) => match string_kind(c1 - 0x40) {
+            Some(kind) => (kind, 1),
+            None => return false,
+        },
+        _ => return false,
+    };
+    sequence_len(kind, &dat[start..]).is_none()
+}
+
+// Splits a sequence off the start of `dat`, which may be introduced either by `ESC` or by an
+// 8-bit C1 control. Returns the number of bytes used, along with the parsed sequence.
+pub(crate) fn parse_bytes(dat: &[u8]) -> Option<(usize, ByteOutput<'_>)> {
+    let (kind, start) = match *dat.first()? {
+        0x1b => match *dat.get(1)? {
+            0x1b => return Some((2, ByteOutput::Escape(AnsiSequence::Escape))),
+            b'[' => (Kind::Csi, 2),
+            b => string_kind(b).map_or((Kind::Esc, 1), |kind| (kind, 2)),
+        },
+        0x9b => (Kind::Csi, 1),
+        c1 @ 0x80..=0x9f => match string_kind(c1 - 0x40) {
+            Some(kind) => (kind, 1),
+            None => {
+                let idx = (c1 - 0x80) as usize;
+                let body = &C1_FINALS[idx..=idx];
+                let seq = Sequence::Esc(Esc {
+                    intermediates: "",
+                    final_byte: (c1 - 0x40) as char,
+                });
+                let out = match interpret(&seq, body) {
+                    Some(esc) => ByteOutput::Escape(esc),
+                    None => ByteOutput::Unknown(seq),
+                };
+                return Some((1, out));
+            }
+        },
+        _ => return None,
+    };
+
+    let (len, c1_st) = sequence_len(kind, &dat[start..])?;
+    let body = core::str::from_utf8(&dat[start..start + len]).ok()?;
+    let seq = parse_body(kind, body, c1_st)?;
+
+    let out = match interpret(&seq, body) {
+        Some(esc) => ByteOutput::Escape(esc),
+        None => ByteOutput::Unknown(seq),
+    };
+    Some((start + len + c1_st as usize, out))
+}
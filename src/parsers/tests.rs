@@ -1,7 +1,13 @@
 use crate::{
-    enums::{AnsiSequence, Output},
-    parsers::parse_escape,
-    traits::AnsiParser,
+    device::DeviceAttribute,
+    enums::{
+        AnsiSequence, ByteOutput, ControlString, Csi, Dcs, Esc, Output, Sequence, SgrParam,
+        Terminator,
+    },
+    modes::{AnsiMode, DecMode, ModeState},
+    palette::{ColorSpec, DynamicColor, PaletteColors, PaletteIndices},
+    parsers::{parse_escape, parse_sequence},
+    traits::{AnsiByteParser, AnsiParser},
 };
 
 use std::fmt::Write;
@@ -53,14 +59,70 @@ test_def_val_parser!(cursor_forward, "\u{1b}[5C");
 test_def_val_parser!(cursor_backward, "\u{1b}[5D");
 test_parser!(cursor_save, "\u{1b}[s");
 test_parser!(cursor_restore, "\u{1b}[u");
+test_def_val_parser!(cursor_next_line_default, "\u{1b}[E");
+test_parser!(cursor_next_line, "\u{1b}[2E");
+test_def_val_parser!(cursor_prev_line_default, "\u{1b}[F");
+test_parser!(cursor_prev_line, "\u{1b}[2F");
+test_def_val_parser!(cursor_column_default, "\u{1b}[G");
+test_parser!(cursor_column, "\u{1b}[40G");
+test_def_val_parser!(char_pos_absolute_default, "\u{1b}[`");
+test_parser!(char_pos_absolute, "\u{1b}[40`");
+test_def_val_parser!(line_pos_absolute_default, "\u{1b}[d");
+test_parser!(line_pos_absolute, "\u{1b}[12d");
+test_def_val_parser!(char_pos_relative_default, "\u{1b}[a");
+test_parser!(char_pos_relative, "\u{1b}[3a");
+test_def_val_parser!(line_pos_relative_default, "\u{1b}[e");
+test_parser!(line_pos_relative, "\u{1b}[3e");
+test_def_val_parser!(cursor_forward_tab_default, "\u{1b}[I");
+test_parser!(cursor_forward_tab, "\u{1b}[2I");
+test_def_val_parser!(cursor_backward_tab_default, "\u{1b}[Z");
+test_parser!(cursor_backward_tab, "\u{1b}[2Z");
 
 test_parser!(erase_display, "\u{1b}[2J");
+test_parser!(erase_display_from_cursor, "\u{1b}[J");
+test_parser!(erase_display_to_cursor, "\u{1b}[1J");
+test_parser!(erase_scrollback, "\u{1b}[3J");
+test_def_val_parser!(erase_display_explicit, "\u{1b}[0J");
 test_parser!(erase_line, "\u{1b}[K");
+test_parser!(erase_line_to_cursor, "\u{1b}[1K");
+test_parser!(erase_line_all, "\u{1b}[2K");
+test_def_val_parser!(erase_line_explicit, "\u{1b}[0K");
+test_parser!(selective_erase_display, "\u{1b}[?J");
+test_parser!(selective_erase_scrollback, "\u{1b}[?3J");
+test_parser!(selective_erase_line, "\u{1b}[?2K");
+test_parser!(erase_chars, "\u{1b}[5X");
+test_def_val_parser!(erase_chars_default, "\u{1b}[X");
+
+test_parser!(insert_chars, "\u{1b}[3@");
+test_def_val_parser!(insert_chars_default, "\u{1b}[@");
+test_parser!(delete_chars, "\u{1b}[3P");
+test_def_val_parser!(delete_chars_default, "\u{1b}[P");
+test_parser!(insert_lines, "\u{1b}[2L");
+test_def_val_parser!(insert_lines_default, "\u{1b}[L");
+test_parser!(delete_lines, "\u{1b}[2M");
+test_def_val_parser!(delete_lines_default, "\u{1b}[M");
+test_parser!(scroll_up, "\u{1b}[4S");
+test_def_val_parser!(scroll_up_default, "\u{1b}[S");
+test_parser!(scroll_down, "\u{1b}[4T");
+test_def_val_parser!(scroll_down_default, "\u{1b}[T");
+test_parser!(repeat_char, "\u{1b}[80b");
+test_def_val_parser!(repeat_char_default, "\u{1b}[b");
 
 test_parser!(set_video_mode_a, "\u{1b}[4m");
 test_parser!(set_video_mode_b, "\u{1b}[4;42m");
 test_parser!(set_video_mode_c, "\u{1b}[4;31;42m");
 test_parser!(set_video_mode_d, "\u{1b}[4;31;42;42;42m");
+test_parser!(set_video_mode_e, "\u{1b}[1;4;31;42m");
+test_parser!(set_video_mode_f, "\u{1b}[0;1;3;4;38;2;255;128;0;48;5;236m");
+test_parser!(set_video_mode_reset, "\u{1b}[m");
+test_parser!(set_video_mode_empty_param, "\u{1b}[;1m");
+test_parser!(set_video_mode_empty_params, "\u{1b}[1;;4;m");
+test_parser!(set_video_mode_sub_param, "\u{1b}[4:3m");
+test_parser!(set_video_mode_sub_color, "\u{1b}[58:2::255:0:0m");
+test_parser!(
+    set_video_mode_sub_mixed,
+    "\u{1b}[1;38:2::10:20:30;48;5;236m"
+);
 
 test_parser!(reset_mode, "\u{1b}[=13l");
 test_parser!(set_mode, "\u{1b}[=7h");
@@ -104,6 +166,103 @@ test_parser!(set_g0_graph, "\u{1b}(2");
 test_parser!(set_g1_graph, "\u{1b})2");
 test_parser!(set_single_shift2, "\u{1b}N");
 test_parser!(set_single_shift3, "\u{1b}O");
+test_parser!(dec_save_cursor, "\u{1b}7");
+test_parser!(dec_restore_cursor, "\u{1b}8");
+test_parser!(full_reset, "\u{1b}c");
+test_parser!(index, "\u{1b}D");
+test_parser!(next_line, "\u{1b}E");
+test_parser!(reverse_index, "\u{1b}M");
+test_parser!(tab_set, "\u{1b}H");
+test_parser!(screen_alignment_test, "\u{1b}#8");
+
+test_parser!(set_title_bel, "\u{1b}]0;title\u{7}");
+test_parser!(set_title_st, "\u{1b}]0;title\u{1b}\\");
+test_parser!(set_icon_name, "\u{1b}]1;icon\u{7}");
+test_parser!(set_window_title, "\u{1b}]2;a;title\u{1b}\\");
+test_parser!(hyperlink, "\u{1b}]8;;https://example.com\u{1b}\\");
+test_parser!(hyperlink_id, "\u{1b}]8;id=1:x=y;https://example.com\u{7}");
+test_parser!(hyperlink_end, "\u{1b}]8;;\u{1b}\\");
+
+test_parser!(palette_query, "\u{1b}]4;1;?\u{7}");
+test_parser!(palette_reply, "\u{1b}]4;255;rgb:ffff/8080/0000\u{1b}\\");
+test_parser!(palette_named, "\u{1b}]4;3;yellow\u{7}");
+test_parser!(reset_palette, "\u{1b}]104;3\u{7}");
+test_parser!(reset_palette_all, "\u{1b}]104\u{7}");
+test_parser!(foreground_query, "\u{1b}]10;?\u{7}");
+test_parser!(background_reply, "\u{1b}]11;rgb:0000/0000/0000\u{1b}\\");
+test_parser!(cursor_color, "\u{1b}]12;#f00\u{7}");
+test_parser!(reset_foreground, "\u{1b}]110\u{7}");
+test_parser!(reset_cursor_color, "\u{1b}]112\u{1b}\\");
+
+test_parser!(clipboard_set, "\u{1b}]52;c;aGVsbG8=\u{7}");
+test_parser!(clipboard_query, "\u{1b}]52;c;?\u{7}");
+test_parser!(clipboard_clear, "\u{1b}]52;ps;\u{1b}\\");
+test_parser!(clipboard_default, "\u{1b}]52;;aGVsbG8=\u{7}");
+
+test_parser!(prompt_start, "\u{1b}]133;A\u{7}");
+test_parser!(prompt_start_options, "\u{1b}]133;A;aid=12;cl=m\u{7}");
+test_parser!(command_start, "\u{1b}]133;B\u{1b}\\");
+test_parser!(command_executed, "\u{1b}]133;C\u{7}");
+test_parser!(command_finished, "\u{1b}]133;D\u{7}");
+test_parser!(command_finished_code, "\u{1b}]133;D;127\u{7}");
+test_parser!(vscode_mark, "\u{1b}]633;D;0\u{7}");
+test_parser!(vscode_command_line, "\u{1b}]633;E;ls\\x3b -l;nonce\u{7}");
+test_parser!(vscode_property, "\u{1b}]633;P;Cwd=/home/user\u{7}");
+test_parser!(working_directory, "\u{1b}]7;file://host/home/user\u{1b}\\");
+test_parser!(working_directory_no_host, "\u{1b}]7;file:///tmp\u{7}");
+test_parser!(current_dir, "\u{1b}]1337;CurrentDir=/home/user\u{7}");
+test_parser!(iterm_set_mark, "\u{1b}]1337;SetMark\u{7}");
+test_parser!(iterm_user_var, "\u{1b}]1337;SetUserVar=foo=YmFy\u{7}");
+test_parser!(
+    iterm_remote_host,
+    "\u{1b}]1337;RemoteHost=user@host\u{1b}\\"
+);
+test_parser!(
+    iterm_file,
+    "\u{1b}]1337;File=name=YS50eHQ=;inline=1:aGk=\u{7}"
+);
+
+test_parser!(request_sgr, "\u{1b}P$qm\u{1b}\\");
+test_parser!(request_cursor_style, "\u{1b}P$q q\u{1b}\\");
+test_parser!(report_sgr, "\u{1b}P1$r0;1m\u{1b}\\");
+test_parser!(report_invalid, "\u{1b}P0$r\u{1b}\\");
+test_parser!(request_termcap, "\u{1b}P+q544e;636f6c6f7273\u{1b}\\");
+test_parser!(report_termcap, "\u{1b}P1+r544e=787465726d\u{1b}\\");
+test_parser!(report_termcap_unknown, "\u{1b}P0+r6b6263\u{1b}\\");
+
+test_parser!(kitty_transmit, "\u{1b}_Gf=100,a=T,m=1;iVBORw0KGgo=\u{1b}\\");
+test_parser!(kitty_delete, "\u{1b}_Ga=d\u{1b}\\");
+test_parser!(kitty_empty_payload, "\u{1b}_Ga=q;\u{1b}\\");
+
+test_parser!(set_alt_screen, "\u{1b}[?1049h");
+test_parser!(set_bracketed_paste, "\u{1b}[?2004h");
+test_parser!(set_sgr_mouse, "\u{1b}[?1000;1006h");
+test_parser!(reset_sync_output, "\u{1b}[?2026l");
+test_parser!(set_insert_mode, "\u{1b}[4h");
+test_parser!(reset_ansi_modes, "\u{1b}[4;20l");
+
+test_parser!(request_sync_output, "\u{1b}[?2026$p");
+test_parser!(request_insert_mode, "\u{1b}[4$p");
+test_parser!(report_sync_output, "\u{1b}[?2026;2$y");
+test_parser!(report_unknown_mode, "\u{1b}[?9999;0$y");
+test_parser!(report_ansi_mode, "\u{1b}[20;4$y");
+
+test_parser!(request_primary_attributes, "\u{1b}[c");
+test_def_val_parser!(request_primary_attributes_zero, "\u{1b}[0c");
+test_parser!(primary_attributes, "\u{1b}[?62;22c");
+test_parser!(primary_attributes_class_only, "\u{1b}[?6c");
+test_parser!(request_secondary_attributes, "\u{1b}[>c");
+test_def_val_parser!(request_secondary_attributes_zero, "\u{1b}[>0c");
+test_parser!(secondary_attributes, "\u{1b}[>41;367;0c");
+test_parser!(request_tertiary_attributes, "\u{1b}[=c");
+test_parser!(tertiary_attributes, "\u{1b}P!|7E565445\u{1b}\\");
+test_parser!(request_device_status, "\u{1b}[5n");
+test_parser!(device_status_ok, "\u{1b}[0n");
+test_parser!(device_status_error, "\u{1b}[3n");
+test_parser!(request_cursor_position, "\u{1b}[6n");
+test_parser!(cursor_position_report, "\u{1b}[24;80R");
+test_parser!(request_terminal_version, "\u{1b}[>q");
+test_parser!(terminal_version, "\u{1b}P>|xterm(367)\u{1b}\\");
 
 #[test]
 fn test_parser_iterator() {
@@ -128,7 +287,7 @@ fn test_default_value() {
     let strings: Vec<_> = "\x1b[H\x1b[123456H\x1b[;123456H\x1b[7asd;1234H\x1b[a;sd7H"
         .ansi_parse()
         .collect();
-    assert_eq!(strings.len(), 5);
+    assert_eq!(strings.len(), 7);
     assert_eq!(strings[0], Output::Escape(AnsiSequence::CursorPos(1, 1)));
     assert_eq!(
         strings[1],
@@ -138,8 +297,17 @@ fn test_default_value() {
         strings[2],
         Output::Escape(AnsiSequence::CursorPos(1, 123456))
     );
-    assert_eq!(strings[3], Output::TextBlock("\x1b[7asd;1234H"));
-    assert_eq!(strings[4], Output::TextBlock("\x1b[a;sd7H"));
+    // `\x1b[7a` and `\x1b[a` are complete sequences of their own, so the rest is just text.
+    assert_eq!(
+        strings[3],
+        Output::Escape(AnsiSequence::CharacterPositionRelative(7))
+    );
+    assert_eq!(strings[4], Output::TextBlock("sd;1234H"));
+    assert_eq!(
+        strings[5],
+        Output::Escape(AnsiSequence::CharacterPositionRelative(1))
+    );
+    assert_eq!(strings[6], Output::TextBlock(";sd7H"));
 }
 
 #[test]
@@ -153,3 +321,942 @@ fn test_escape() {
         ]
     );
 }
+
+#[test]
+fn test_graphics_mode_params() {
+    let parsed: Vec<_> = "\x1b[;1m\x1b[0;1;3;4;38;2;255;128;0;48;5;236mtext"
+        .ansi_parse()
+        .collect();
+
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(
+        parsed[0],
+        Output::Escape(AnsiSequence::SetGraphicsMode(
+            heapless::Vec::from_slice(&[SgrParam::Param(None), SgrParam::Param(Some(1))]).unwrap()
+        ))
+    );
+    assert_eq!(
+        parsed[1],
+        Output::Escape(AnsiSequence::SetGraphicsMode(
+            [0, 1, 3, 4, 38, 2, 255, 128, 0, 48, 5, 236]
+                .iter()
+                .map(|&val| SgrParam::from(val))
+                .collect()
+        ))
+    );
+    assert_eq!(parsed[2], Output::TextBlock("text"));
+}
+
+#[test]
+fn test_graphics_mode_invalid() {
+    // Out of range values and parameter lists longer than the buffer fail cleanly.
+    assert!(parse_escape("\x1b[256m").is_err());
+
+    // 64 parameters fit, sub-parameters included, while 65 don't.
+    let mut long = String::from("\x1b[1");
+    for _ in 1..32 {
+        long.push_str(";1");
+    }
+    assert!(parse_escape(&(long.clone() + ";1m")).is_ok());
+    for _ in 32..63 {
+        long.push_str(":1");
+    }
+    match parse_escape(&(long.clone() + ":1m")) {
+        Ok((_, AnsiSequence::SetGraphicsMode(params))) => assert_eq!(params.len(), 64),
+        other => panic!("{:?}", other),
+    }
+    assert!(parse_escape(&(long + ";1;1m")).is_err());
+}
+
+#[test]
+fn test_graphics_mode_sub_params() {
+    use SgrParam::*;
+
+    let parsed = parse_escape("\x1b[1;38:2::10:20:30m").unwrap().1;
+    let expected = [
+        Param(Some(1)),
+        Param(Some(38)),
+        SubParam(Some(2)),
+        SubParam(None),
+        SubParam(Some(10)),
+        SubParam(Some(20)),
+        SubParam(Some(30)),
+    ];
+    assert_eq!(
+        parsed,
+        AnsiSequence::SetGraphicsMode(heapless::Vec::from_slice(&expected).unwrap())
+    );
+
+    // The separator is part of the parameters, so these two must not compare equal.
+    assert_ne!(
+        parse_escape("\x1b[38;2;1;2;3m").unwrap().1,
+        parse_escape("\x1b[38:2:1:2:3m").unwrap().1
+    );
+}
+
+macro_rules! test_sequence {
+    ($name:ident, $string:expr, $expected:expr) => {
+        #[test]
+        fn $name() {
+            let ret = parse_sequence($string);
+
+            assert!(ret.is_ok());
+            let (rest, ret) = ret.unwrap();
+            assert_eq!(rest, "");
+            assert_eq!(ret, $expected);
+            assert_eq!(format!("{}", ret), $string);
+        }
+    };
+}
+
+test_sequence!(
+    sequence_csi,
+    "\u{1b}[?1049h",
+    Sequence::Csi(Csi {
+        private_marker: Some('?'),
+        params: "1049",
+        intermediates: "",
+        final_byte: 'h',
+    })
+);
+test_sequence!(
+    sequence_csi_intermediate,
+    "\u{1b}[?2026$p",
+    Sequence::Csi(Csi {
+        private_marker: Some('?'),
+        params: "2026",
+        intermediates: "$",
+        final_byte: 'p',
+    })
+);
+test_sequence!(
+    sequence_esc,
+    "\u{1b}#8",
+    Sequence::Esc(Esc {
+        intermediates: "#",
+        final_byte: '8',
+    })
+);
+test_sequence!(
+    sequence_osc_bel,
+    "\u{1b}]0;title\u{7}",
+    Sequence::Osc(ControlString {
+        data: "0;title",
+        terminator: Terminator::Bel,
+    })
+);
+test_sequence!(
+    sequence_osc_st,
+    "\u{1b}]8;;https://example.com\u{1b}\\",
+    Sequence::Osc(ControlString {
+        data: "8;;https://example.com",
+        terminator: Terminator::St,
+    })
+);
+test_sequence!(
+    sequence_dcs,
+    "\u{1b}P1$r0;1m\u{1b}\\",
+    Sequence::Dcs(Dcs {
+        private_marker: None,
+        params: "1",
+        intermediates: "$",
+        final_byte: 'r',
+        data: "0;1m",
+        terminator: Terminator::St,
+    })
+);
+test_sequence!(
+    sequence_apc,
+    "\u{1b}_Gf=100;AAAA\u{1b}\\",
+    Sequence::Apc(ControlString {
+        data: "Gf=100;AAAA",
+        terminator: Terminator::St,
+    })
+);
+test_sequence!(
+    sequence_pm,
+    "\u{1b}^note\u{1b}\\",
+    Sequence::Pm(ControlString {
+        data: "note",
+        terminator: Terminator::St,
+    })
+);
+test_sequence!(
+    sequence_sos,
+    "\u{1b}Xstring\u{1b}\\",
+    Sequence::Sos(ControlString {
+        data: "string",
+        terminator: Terminator::St,
+    })
+);
+
+#[test]
+fn test_sequence_malformed() {
+    assert!(parse_sequence("\x1b[1\n2m").is_err());
+    assert!(parse_sequence("\x1b]0;title\x1bx").is_err());
+    assert!(parse_sequence("\x1b\x1b").is_err());
+    assert!(parse_sequence("\x1b[12").is_err());
+}
+
+#[test]
+fn test_parser_iterator_unknown() {
+    let text = "a\x1b[?1049hb\x1b]0;title\x07c\x1b[1mdone";
+    let parsed: Vec<_> = text.ansi_parse().collect();
+
+    assert_eq!(parsed.len(), 7);
+    assert!(parsed
+        .iter()
+        .all(|out| !matches!(out, Output::TextBlock(txt) if txt.contains('\x1b'))));
+    assert_eq!(
+        parsed[5],
+        Output::Escape(AnsiSequence::SetGraphicsMode(
+            heapless::Vec::from_slice(&[SgrParam::from(1)]).unwrap()
+        ))
+    );
+
+    let rebuilt: String = parsed.iter().map(|out| format!("{}", out)).collect();
+    assert_eq!(rebuilt, text);
+}
+
+fn sgr(params: &[u8]) -> AnsiSequence<'static> {
+    AnsiSequence::SetGraphicsMode(params.iter().map(|&val| SgrParam::from(val)).collect())
+}
+
+#[test]
+fn test_byte_parser() {
+    let parsed: Vec<_> = b"ab\xff\x1b[31mcd\xc3".ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ByteOutput::TextBlock(b"ab\xff"),
+            ByteOutput::Escape(sgr(&[31])),
+            ByteOutput::TextBlock(b"cd\xc3"),
+        ]
+    );
+}
+
+#[test]
+fn test_byte_parser_matches_str() {
+    let text = "a\x1b[?1049hb\x1b]0;t\u{fc}tle\x07c\x1b[1m\x1b\x1b[33md\x1b[7asd\x1b";
+
+    let from_str: Vec<_> = text.ansi_parse().collect();
+    let from_bytes: Vec<_> = text.as_bytes().ansi_parse().collect();
+    assert_eq!(from_str.len(), from_bytes.len());
+
+    for (a, b) in from_str.into_iter().zip(from_bytes) {
+        match (a, b) {
+            (Output::TextBlock(a), ByteOutput::TextBlock(b)) => assert_eq!(a.as_bytes(), b),
+            (Output::Escape(a), ByteOutput::Escape(b)) => assert_eq!(a, b),
+            (Output::Unknown(a), ByteOutput::Unknown(b)) => assert_eq!(a, b),
+            (a, b) => panic!("{:?} != {:?}", a, b),
+        }
+    }
+}
+
+#[test]
+fn test_byte_parser_c1() {
+    let parsed: Vec<_> = b"\x9b1;4m\xc3\x9b\x9d0;title\x9c\x8e\x90$qm\x1b\\"
+        .ansi_parse()
+        .collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ByteOutput::Escape(sgr(&[1, 4])),
+            // A UTF-8 encoded character, not a C1 control.
+            ByteOutput::TextBlock("\u{db}".as_bytes()),
+            ByteOutput::Escape(AnsiSequence::SetIconNameAndWindowTitle(
+                "title",
+                Terminator::St
+            )),
+            ByteOutput::Escape(AnsiSequence::SetSingleShift2),
+            ByteOutput::Escape(AnsiSequence::RequestStatusString("m", Terminator::St)),
+        ]
+    );
+}
+
+#[test]
+fn test_byte_parser_non_ascii() {
+    // `本` and `Ŝ` both hold a 0x9c, which is not an 8-bit ST there.
+    let input = [
+        "\x1b]0;日本\x07a\x1b]8;;file:///Ŝ\x1b\\b\x1bP$q本\x1b\\".as_bytes(),
+        b"\x9d2;",
+        "Ŝ".as_bytes(),
+        b"\x9c",
+    ]
+    .concat();
+    let parsed: Vec<_> = input.ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ByteOutput::Escape(AnsiSequence::SetIconNameAndWindowTitle(
+                "日本",
+                Terminator::Bel
+            )),
+            ByteOutput::TextBlock(b"a"),
+            ByteOutput::Escape(AnsiSequence::Hyperlink(
+                crate::enums::Hyperlink {
+                    params: "",
+                    uri: "file:///Ŝ",
+                },
+                Terminator::St
+            )),
+            ByteOutput::TextBlock(b"b"),
+            ByteOutput::Escape(AnsiSequence::RequestStatusString("本", Terminator::St)),
+            ByteOutput::Escape(AnsiSequence::SetWindowTitle("Ŝ", Terminator::St)),
+        ]
+    );
+
+    let text = "\x1bP1$r本\x1b\\";
+    let from_str: Vec<_> = text.ansi_parse().collect();
+    let from_bytes: Vec<_> = text.as_bytes().ansi_parse().collect();
+    assert_eq!(from_str.len(), 1);
+    match (&from_str[0], &from_bytes[..]) {
+        (Output::Escape(a), [ByteOutput::Escape(b)]) => assert_eq!(a, b),
+        (a, b) => panic!("{:?} != {:?}", a, b),
+    }
+}
+
+#[test]
+fn test_byte_parser_invalid() {
+    // Invalid UTF-8 inside of a sequence, and a sequence cut short.
+    let parsed: Vec<_> = b"\x1b]0;\xfftitle\x07x\x9b31".ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ByteOutput::TextBlock(b"\x1b]0;\xfftitle\x07x"),
+            ByteOutput::TextBlock(b"\x9b31"),
+        ]
+    );
+}
+
+#[test]
+fn test_osc() {
+    use crate::enums::Hyperlink;
+
+    let parsed: Vec<_> =
+        "\x1b]0;title\x07\x1b]8;id=abc;https://example.com\x1b\\link\x1b]8;;\x1b\\"
+            .ansi_parse()
+            .collect();
+
+    assert_eq!(
+        parsed,
+        vec![
+            Output::Escape(AnsiSequence::SetIconNameAndWindowTitle(
+                "title",
+                Terminator::Bel
+            )),
+            Output::Escape(AnsiSequence::Hyperlink(
+                Hyperlink {
+                    params: "id=abc",
+                    uri: "https://example.com",
+                },
+                Terminator::St
+            )),
+            Output::TextBlock("link"),
+            Output::Escape(AnsiSequence::Hyperlink(
+                Hyperlink {
+                    params: "",
+                    uri: "",
+                },
+                Terminator::St
+            )),
+        ]
+    );
+
+    match &parsed[1] {
+        Output::Escape(AnsiSequence::Hyperlink(link, _)) => assert_eq!(link.id(), Some("abc")),
+        _ => unreachable!(),
+    }
+    match &parsed[3] {
+        Output::Escape(AnsiSequence::Hyperlink(link, _)) => assert_eq!(link.id(), None),
+        _ => unreachable!(),
+    }
+
+    // Unknown OSC numbers are still split out whole.
+    assert!(parse_escape("\x1b]777;notify\x07").is_err());
+    assert_eq!("\x1b]777;notify\x07".ansi_parse().count(), 1);
+}
+
+#[test]
+fn test_osc_colors() {
+    assert_eq!(
+        parse_escape("\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\"),
+        Ok((
+            "",
+            AnsiSequence::DynamicColor(
+                DynamicColor::Background,
+                heapless::Vec::from_slice(&[ColorSpec::parse("rgb:1e1e/1e1e/2e2e")]).unwrap(),
+                Terminator::St
+            )
+        ))
+    );
+    assert_eq!(
+        parse_escape("\x1b]4;12;?\x07"),
+        Ok((
+            "",
+            AnsiSequence::PaletteColor(PaletteColors::new("12;?").unwrap(), Terminator::Bel)
+        ))
+    );
+    assert_eq!(
+        parse_escape("\x1b]104\x07"),
+        Ok((
+            "",
+            AnsiSequence::ResetPaletteColor(PaletteIndices::new("").unwrap(), Terminator::Bel)
+        ))
+    );
+    assert_eq!(
+        parse_escape("\x1b]111\x07"),
+        Ok((
+            "",
+            AnsiSequence::ResetDynamicColor(DynamicColor::Background, Terminator::Bel)
+        ))
+    );
+
+    // Out of range palette entries, unpaired specs, colors past the cursor color and trailing
+    // data are left uninterpreted.
+    for seq in &[
+        "\x1b]4;256;?\x07",
+        "\x1b]4;1;?;2\x07",
+        "\x1b]104;x\x07",
+        "\x1b]104;1;\x07",
+        "\x1b]110;?\x07",
+        "\x1b]12;?;?\x07",
+        "\x1b]13;?\x07",
+    ] {
+        let parsed: Vec<_> = seq.ansi_parse().collect();
+        match parsed.as_slice() {
+            [Output::Unknown(_)] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+}
+
+#[test]
+fn test_osc_color_lists() {
+    let seq = "\x1b]4;0;?;1;rgb:ff/00/00\x07";
+    match parse_escape(seq) {
+        Ok(("", AnsiSequence::PaletteColor(colors, Terminator::Bel))) => {
+            assert_eq!(
+                colors.entries().collect::<Vec<_>>(),
+                vec![(0, ColorSpec::Query), (1, ColorSpec::parse("rgb:ff/00/00"))]
+            );
+            assert_eq!(
+                format!("{}", AnsiSequence::PaletteColor(colors, Terminator::Bel)),
+                seq
+            );
+        }
+        other => panic!("{:?}", other),
+    }
+
+    let seq = "\x1b]104;1;2\x07";
+    match parse_escape(seq) {
+        Ok(("", AnsiSequence::ResetPaletteColor(indices, Terminator::Bel))) => {
+            assert_eq!(indices.entries().collect::<Vec<_>>(), vec![1, 2]);
+            assert_eq!(
+                format!(
+                    "{}",
+                    AnsiSequence::ResetPaletteColor(indices, Terminator::Bel)
+                ),
+                seq
+            );
+        }
+        other => panic!("{:?}", other),
+    }
+
+    let seq = "\x1b]10;?;?\x07";
+    let parsed = parse_escape(seq).unwrap().1;
+    assert_eq!(
+        parsed,
+        AnsiSequence::DynamicColor(
+            DynamicColor::Foreground,
+            heapless::Vec::from_slice(&[ColorSpec::Query, ColorSpec::Query]).unwrap(),
+            Terminator::Bel
+        )
+    );
+    assert_eq!(format!("{}", parsed), seq);
+    let parsed = parse_escape("\x1b]11;red;blue\x07").unwrap().1;
+    assert!(matches!(parsed, AnsiSequence::DynamicColor(_, specs, _) if specs.len() == 2));
+
+    // The whole palette can be set at once.
+    let mut seq = String::from("\x1b]4");
+    for idx in 0..256 {
+        write!(seq, ";{};#{:06x}", idx, idx).unwrap();
+    }
+    seq.push('\x07');
+    let parsed = parse_escape(&seq);
+    match parsed {
+        Ok(("", AnsiSequence::PaletteColor(colors, _))) => {
+            assert_eq!(colors.entries().count(), 256);
+            assert_eq!(
+                colors.entries().last().unwrap().1.rgb().unwrap().to_rgb8(),
+                (0, 0, 255)
+            );
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_osc_clipboard() {
+    use crate::clipboard::Clipboard;
+
+    let parsed: Vec<_> = "\x1b]52;c;aGVsbG8=\x07".ansi_parse().collect();
+    match parsed.as_slice() {
+        [Output::Escape(AnsiSequence::Clipboard(clip, Terminator::Bel))] => {
+            assert_eq!(
+                *clip,
+                Clipboard {
+                    selection: "c",
+                    data: "aGVsbG8="
+                }
+            );
+            assert_eq!(clip.decode().unwrap(), b"hello");
+        }
+        other => panic!("{:?}", other),
+    }
+
+    for seq in &[
+        "\x1b]52;x;aGVsbG8=\x07",
+        "\x1b]52;c;a b\x07",
+        "\x1b]52;c\x07",
+    ] {
+        match seq.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(_)] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+}
+
+#[test]
+fn test_osc_shell_integration() {
+    use crate::shell::{FileUrl, MarkKind, ShellMark, VsCodeMark};
+
+    assert_eq!(
+        parse_escape("\x1b]133;D;2\x07"),
+        Ok((
+            "",
+            AnsiSequence::SemanticPrompt(
+                ShellMark {
+                    kind: MarkKind::CommandFinished,
+                    params: Some("2"),
+                },
+                Terminator::Bel
+            )
+        ))
+    );
+    assert_eq!(
+        parse_escape("\x1b]633;P;Cwd=/tmp\x07"),
+        Ok((
+            "",
+            AnsiSequence::VsCodeShellIntegration(
+                VsCodeMark::Property("Cwd", "/tmp"),
+                Terminator::Bel
+            )
+        ))
+    );
+    assert_eq!(
+        parse_escape("\x1b]7;file://host/a%20b\x07"),
+        Ok((
+            "",
+            AnsiSequence::WorkingDirectory(
+                FileUrl {
+                    host: "host",
+                    path: "/a%20b",
+                },
+                Terminator::Bel
+            )
+        ))
+    );
+
+    for seq in &[
+        "\x1b]133;Z\x07",
+        "\x1b]133;AB\x07",
+        "\x1b]633;E\x07",
+        "\x1b]7;/tmp\x07",
+        "\x1b]7;file://host\x07",
+    ] {
+        match seq.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(_)] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+}
+
+#[test]
+fn test_dcs_queries() {
+    assert_eq!(
+        parse_escape("\x1bP$q m\x1b\\"),
+        Ok(("", AnsiSequence::RequestStatusString(" m", Terminator::St)))
+    );
+    assert_eq!(
+        parse_escape("\x1bP1$r2 q\x1b\\"),
+        Ok((
+            "",
+            AnsiSequence::StatusStringReport(true, "2 q", Terminator::St)
+        ))
+    );
+
+    let parsed: Vec<_> = "\x1bP1+r544e=787465726d\x1b\\".ansi_parse().collect();
+    match parsed.as_slice() {
+        [Output::Escape(AnsiSequence::TermcapReport(true, caps, Terminator::St))] => {
+            assert!(caps.get("TN").unwrap().is("xterm"));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // tmux passthrough and other device control strings are split out whole.
+    for seq in &[
+        "\x1bPtmux;\x1b\x1b]0;title\x07\x1b\\",
+        "\x1bP2$r0m\x1b\\",
+        "\x1bP+q544e=787465726d\x1b\\",
+        "\x1bP1+rTN\x1b\\",
+    ] {
+        match seq.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(Sequence::Dcs(_))] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+        match seq.as_bytes().ansi_parse().collect::<Vec<_>>().as_slice() {
+            [ByteOutput::Unknown(Sequence::Dcs(_))] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+
+    // Only ST ends a device control string.
+    let parsed: Vec<_> = "\x1bPtmux;\x1b\x1b]0;title\x07\x1b\\"
+        .ansi_parse()
+        .collect();
+    match parsed.as_slice() {
+        [Output::Unknown(Sequence::Dcs(dcs))] => {
+            assert_eq!(dcs.data, "mux;\x1b\x1b]0;title\x07");
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_apc() {
+    use crate::kitty::KittyGraphics;
+
+    assert_eq!(
+        parse_escape("\x1b_Ga=p,i=3\x1b\\"),
+        Ok((
+            "",
+            AnsiSequence::KittyGraphics(
+                KittyGraphics {
+                    control: "a=p,i=3",
+                    payload: None,
+                },
+                Terminator::St
+            )
+        ))
+    );
+
+    for seq in &["\x1b_other\x1b\\", "\x1b_Ga=T;@@\x1b\\"] {
+        match seq.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(Sequence::Apc(_))] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+}
+
+#[test]
+fn test_osc_iterm() {
+    let parsed: Vec<_> = "\x1b]1337;File=inline=1:aGk=\x07".ansi_parse().collect();
+    match parsed.as_slice() {
+        [Output::Escape(AnsiSequence::ITerm(cmd, Terminator::Bel))] => {
+            assert_eq!(cmd.file().unwrap().decode().unwrap(), b"hi");
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // CurrentDir keeps its own variant.
+    assert!(matches!(
+        parse_escape("\x1b]1337;CurrentDir=/tmp\x07"),
+        Ok(("", AnsiSequence::CurrentDir("/tmp", Terminator::Bel)))
+    ));
+
+    match "\x1b]1337;\x07".ansi_parse().collect::<Vec<_>>().as_slice() {
+        [Output::Unknown(_)] => (),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_erase() {
+    use crate::enums::EraseMode;
+
+    let parsed: Vec<_> = "\x1b[0K\x1b[3J\x1b[?1J\x1b[X".ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        vec![
+            Output::Escape(AnsiSequence::EraseInLine(EraseMode::ToEnd)),
+            Output::Escape(AnsiSequence::EraseInDisplay(EraseMode::Scrollback)),
+            Output::Escape(AnsiSequence::SelectiveEraseInDisplay(EraseMode::ToStart)),
+            Output::Escape(AnsiSequence::EraseCharacters(1)),
+        ]
+    );
+
+    // Lines have no scrollback, and there is no fifth mode.
+    for seq in &["\x1b[3K", "\x1b[?3K", "\x1b[4J", "\x1b[1;2J"] {
+        match seq.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(_)] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+
+    // Modes that don't fit are not taken as the default.
+    for text in &[
+        "\x1b[99999999999J",
+        "\x1b[99999999999K",
+        "\x1b[?99999999999K",
+    ] {
+        match text.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(Sequence::Csi(csi))] => assert!(csi.params.ends_with("99999999999")),
+            other => panic!("{:?} parsed as {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn test_line_editing() {
+    let parsed: Vec<_> = "\x1b[@\x1b[5Pab\x1b[b\x1b[?5S".ansi_parse().collect();
+    assert_eq!(parsed.len(), 5);
+    assert_eq!(parsed[0], Output::Escape(AnsiSequence::InsertCharacters(1)));
+    assert_eq!(parsed[1], Output::Escape(AnsiSequence::DeleteCharacters(5)));
+    assert_eq!(parsed[2], Output::TextBlock("ab"));
+    assert_eq!(parsed[3], Output::Escape(AnsiSequence::RepeatCharacter(1)));
+    match &parsed[4] {
+        Output::Unknown(Sequence::Csi(csi)) => assert_eq!(csi.private_marker, Some('?')),
+        other => panic!("{:?}", other),
+    }
+
+    // Counts that don't fit are not taken as the default.
+    for text in &[
+        "[99999999999P",
+        "[99999999999L",
+        "[99999999999b",
+        "[99999999999E",
+    ] {
+        match text.ansi_parse().next() {
+            Some(Output::Unknown(Sequence::Csi(csi))) => assert_eq!(csi.params, &text[2..13]),
+            other => panic!("{:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_cursor_count_overflow() {
+    // The original cursor movements share the count parser, so a count that doesn't fit is no
+    // longer read as 1 there either.
+    for seq in &[
+        "\x1b[99999999999A",
+        "\x1b[99999999999B",
+        "\x1b[99999999999C",
+        "\x1b[99999999999D",
+        "\x1b[99999999999;1H",
+        "\x1b[1;99999999999f",
+    ] {
+        match seq.ansi_parse().collect::<Vec<_>>().as_slice() {
+            [Output::Unknown(Sequence::Csi(_))] => (),
+            other => panic!("{:?} parsed as {:?}", seq, other),
+        }
+    }
+    assert_eq!(
+        parse_escape("\x1b[4294967295A"),
+        Ok(("", AnsiSequence::CursorUp(u32::MAX)))
+    );
+}
+
+#[test]
+fn test_single_escapes() {
+    let parsed: Vec<_> = "\x1b7\x1b[1;1Htext\x1bM\x1b8\x1b#3".ansi_parse().collect();
+    assert_eq!(
+        parsed[..5],
+        [
+            Output::Escape(AnsiSequence::DecSaveCursor),
+            Output::Escape(AnsiSequence::CursorPos(1, 1)),
+            Output::TextBlock("text"),
+            Output::Escape(AnsiSequence::ReverseIndex),
+            Output::Escape(AnsiSequence::DecRestoreCursor),
+        ]
+    );
+    // Double height lines and the like are still left uninterpreted.
+    assert!(matches!(parsed[5], Output::Unknown(Sequence::Esc(_))));
+
+    // The 8-bit C1 forms of IND, NEL, HTS and RI mean the same.
+    let parsed: Vec<_> = b"\x84\x85\x88\x8d".ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ByteOutput::Escape(AnsiSequence::Index),
+            ByteOutput::Escape(AnsiSequence::NextLine),
+            ByteOutput::Escape(AnsiSequence::TabSet),
+            ByteOutput::Escape(AnsiSequence::ReverseIndex),
+        ]
+    );
+}
+
+#[test]
+fn test_modes() {
+    let parsed: Vec<_> = "\x1b[?25l\x1b[?25;1049h\x1b[20h\x1b[?h\x1b[?1;;2h"
+        .ansi_parse()
+        .collect();
+    // The single mode sequences keep their own variants.
+    assert_eq!(parsed[0], Output::Escape(AnsiSequence::HideCursor));
+    match &parsed[1] {
+        Output::Escape(AnsiSequence::DecPrivateMode(modes, true)) => {
+            assert_eq!(
+                modes[..],
+                [DecMode::ShowCursor, DecMode::AltScreenSaveCursor]
+            );
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(parsed[2], Output::Escape(AnsiSequence::SetNewLineMode));
+    // Empty modes aren't allowed.
+    assert!(matches!(parsed[3], Output::Unknown(_)));
+    assert!(matches!(parsed[4], Output::Unknown(_)));
+
+    let many = format!("\x1b[?{}h", ["1"; 17].join(";"));
+    assert!(parse_escape(&many).is_err());
+
+    let parsed: Vec<_> = "\x1b[2;12;99l".ansi_parse().collect();
+    match &parsed[0] {
+        Output::Escape(AnsiSequence::AnsiMode(modes, false)) => assert_eq!(
+            modes[..],
+            [
+                AnsiMode::KeyboardLock,
+                AnsiMode::SendReceive,
+                AnsiMode::Other(99)
+            ]
+        ),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_mode_reports() {
+    let parsed: Vec<_> = "\x1b[?2026;1$y\x1b[12;3$y\x1b[?25;5$y\x1b[?2026$p"
+        .ansi_parse()
+        .collect();
+    assert_eq!(
+        parsed[..2],
+        [
+            Output::Escape(AnsiSequence::DecModeReport(
+                DecMode::SynchronizedOutput,
+                ModeState::Set
+            )),
+            Output::Escape(AnsiSequence::AnsiModeReport(
+                AnsiMode::SendReceive,
+                ModeState::PermanentlySet
+            )),
+        ]
+    );
+    // There is no fifth state.
+    assert!(matches!(parsed[2], Output::Unknown(_)));
+    assert_eq!(
+        parsed[3],
+        Output::Escape(AnsiSequence::RequestDecMode(DecMode::SynchronizedOutput))
+    );
+}
+
+#[test]
+fn test_device_reports() {
+    let parsed: Vec<_> =
+        "\x1b[?64;1;4;6;22;52c\x1b[24;80R\x1bP>|WezTerm 20240203\x1b\\\x1bP!|zz\x1b\\"
+            .ansi_parse()
+            .collect();
+    match &parsed[0] {
+        Output::Escape(AnsiSequence::PrimaryAttributes(64, features)) => assert_eq!(
+            features[..],
+            [
+                DeviceAttribute::Columns132,
+                DeviceAttribute::Sixel,
+                DeviceAttribute::SelectiveErase,
+                DeviceAttribute::AnsiColor,
+                DeviceAttribute::Other(52),
+            ]
+        ),
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(
+        parsed[1..3],
+        [
+            Output::Escape(AnsiSequence::CursorPositionReport(24, 80)),
+            Output::Escape(AnsiSequence::TerminalVersion(
+                "WezTerm 20240203",
+                Terminator::St
+            )),
+        ]
+    );
+    // Unit ids are hex.
+    assert!(matches!(parsed[3], Output::Unknown(Sequence::Dcs(_))));
+
+    // Replies can come back in 8-bit form too.
+    let parsed: Vec<_> = b"\x9b1;1R\x9b>1;10;0c".ansi_parse().collect();
+    assert_eq!(
+        parsed,
+        vec![
+            ByteOutput::Escape(AnsiSequence::CursorPositionReport(1, 1)),
+            ByteOutput::Escape(AnsiSequence::SecondaryAttributes(1, 10, 0)),
+        ]
+    );
+}
+
+#[test]
+fn test_primary_attributes_lenient() {
+    // kitty ends its reply with an empty code, which is kept so the reply is written back as
+    // it came.
+    assert_eq!(
+        "\x1b[?62;c".ansi_parse().next(),
+        Some(Output::Escape(AnsiSequence::PrimaryAttributes(
+            62,
+            heapless::Vec::from_slice(&[DeviceAttribute::Empty]).unwrap()
+        )))
+    );
+    let reply = "\x1b[?65;;4;c";
+    let parsed = parse_escape(reply).unwrap().1;
+    assert_eq!(
+        parsed,
+        AnsiSequence::PrimaryAttributes(
+            65,
+            heapless::Vec::from_slice(&[
+                DeviceAttribute::Empty,
+                DeviceAttribute::Sixel,
+                DeviceAttribute::Empty
+            ])
+            .unwrap()
+        )
+    );
+    assert_eq!(format!("{}", parsed), reply);
+
+    // A long reply, like the ones xterm gives with every option compiled in.
+    let reply = "\x1b[?62;1;2;6;9;15;16;17;18;21;22;28;29;52;4;8;7c";
+    match reply.ansi_parse().next() {
+        Some(Output::Escape(AnsiSequence::PrimaryAttributes(62, features))) => {
+            assert_eq!(features.len(), 16);
+            assert_eq!(features[0], DeviceAttribute::Columns132);
+            assert_eq!(features[15], DeviceAttribute::Other(7));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // 32 features fit, while a reply with more is left uninterpreted.
+    let mut reply = String::from("\x1b[?65");
+    for _ in 0..32 {
+        reply.push_str(";4");
+    }
+    reply.push('c');
+    match reply.ansi_parse().next() {
+        Some(Output::Escape(AnsiSequence::PrimaryAttributes(65, features))) => {
+            assert_eq!(features.len(), 32);
+            assert_eq!(features[31], DeviceAttribute::Sixel);
+        }
+        other => panic!("{:?}", other),
+    }
+    reply.insert_str(reply.len() - 1, ";4");
+    match reply.ansi_parse().next() {
+        Some(Output::Unknown(Sequence::Csi(csi))) => assert_eq!(csi.final_byte, 'c'),
+        other => panic!("{:?}", other),
+    };
+}
@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    AnsiByteParser, AnsiSequence, ByteOutput, ColorSpec, DecMode, DeviceAttribute, DynamicColor,
+    ModeState, RgbColor, Sequence, Terminator,
+};
+
+use core::ops::Range;
+use heapless::consts::U32;
+use std::{
+    io::{self, Read, Write},
+    mem,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+///Asks a terminal questions, and reads back its answers.
+///
+///Every query is followed by a request for the primary device attributes (DA1), which all
+///terminals answer, and which they answer in order. Once that reply comes back, any reply to
+///the query has come back too, so there is no need for a timeout. A query that the terminal
+///doesn't know about is simply left unanswered.
+///
+///Whatever else is read along the way, such as keys the user pressed, is kept aside until it
+///is taken with `take_unrelated`. Anything read past the answers to a query is looked through
+///by the next one first, so the answers to several queries can also arrive at once.
+#[derive(Debug)]
+pub struct TerminalProbe<R, W> {
+    reader: R,
+    writer: W,
+    //What was read but not yet looked through, which can run past the answers to a query.
+    buf: Vec<u8>,
+    //The answer to the last query.
+    reply: Vec<u8>,
+    unrelated: Vec<u8>,
+}
+
+//Where the reply to a query and the DA1 reply that followed it were found.
+struct Found {
+    reply: Option<Range<usize>>,
+    fence: Range<usize>,
+}
+
+impl<R: Read, W: Write> TerminalProbe<R, W> {
+    ///Reads answers from `reader`, and writes queries to `writer`. For a terminal these are
+    ///usually its input and output, which should be in raw mode so the answers aren't echoed
+    ///or held back until the end of a line.
+    pub fn new(reader: R, writer: W) -> TerminalProbe<R, W> {
+        TerminalProbe {
+            reader,
+            writer,
+            buf: Vec::new(),
+            reply: Vec::new(),
+            unrelated: Vec::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+
+    ///Everything that was read but wasn't an answer, in the order it arrived.
+    pub fn take_unrelated(&mut self) -> Vec<u8> {
+        self.unrelated.append(&mut self.buf);
+        mem::take(&mut self.unrelated)
+    }
+
+    ///Sends `query`, and returns the first reply that `is_reply` picks out, if the terminal
+    ///sent one.
+    ///
+    ///Fails if writing fails, or if reading fails or ends before the terminal has answered.
+    pub fn query<F>(
+        &mut self,
+        query: &AnsiSequence<'_>,
+        mut is_reply: F,
+    ) -> io::Result<Option<AnsiSequence<'_>>>
+    where
+        F: FnMut(&AnsiSequence<'_>) -> bool,
+    {
+        write!(self.writer, "{}", query)?;
+        //A DA1 request is its own fence.
+        if *query != AnsiSequence::RequestPrimaryAttributes {
+            write!(self.writer, "{}", AnsiSequence::RequestPrimaryAttributes)?;
+        }
+        self.writer.flush()?;
+
+        let mut chunk = [0; 1024];
+        let found = loop {
+            if let Some(found) = find(&self.buf, &mut is_reply) {
+                break found;
+            }
+            let len = match self.reader.read(&mut chunk) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the terminal didn't answer",
+                    ))
+                }
+                Ok(len) => len,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.buf.extend_from_slice(&chunk[..len]);
+        };
+
+        let reply = found.reply.unwrap_or(found.fence.start..found.fence.start);
+        self.unrelated.extend_from_slice(&self.buf[..reply.start]);
+        if reply.end < found.fence.start {
+            self.unrelated
+                .extend_from_slice(&self.buf[reply.end..found.fence.start]);
+        }
+        self.reply.clear();
+        self.reply.extend_from_slice(&self.buf[reply]);
+        self.buf.drain(..found.fence.end);
+
+        match self.reply.ansi_parse().next() {
+            Some(ByteOutput::Escape(seq)) => Ok(Some(seq)),
+            _ => Ok(None),
+        }
+    }
+
+    ///The line and column of the cursor, through CPR.
+    pub fn cursor_position(&mut self) -> io::Result<Option<(u32, u32)>> {
+        let reply = self.query(&AnsiSequence::RequestCursorPosition, |seq| {
+            matches!(seq, AnsiSequence::CursorPositionReport(..))
+        })?;
+        match reply {
+            Some(AnsiSequence::CursorPositionReport(line, col)) => Ok(Some((line, col))),
+            _ => Ok(None),
+        }
+    }
+
+    ///The state of a DEC private mode, through DECRQM. Terminals that don't support DECRQM
+    ///don't answer at all, which gives `None`.
+    pub fn mode(&mut self, mode: DecMode) -> io::Result<Option<ModeState>> {
+        let reply = self.query(
+            &AnsiSequence::RequestDecMode(mode),
+            |seq| matches!(seq, AnsiSequence::DecModeReport(reported, _) if *reported == mode),
+        )?;
+        match reply {
+            Some(AnsiSequence::DecModeReport(_, state)) => Ok(Some(state)),
+            _ => Ok(None),
+        }
+    }
+
+    ///The device class and features, through DA1.
+    pub fn primary_attributes(
+        &mut self,
+    ) -> io::Result<Option<(u16, heapless::Vec<DeviceAttribute, U32>)>> {
+        let reply = self.query(&AnsiSequence::RequestPrimaryAttributes, |seq| {
+            matches!(seq, AnsiSequence::PrimaryAttributes(..))
+        })?;
+        match reply {
+            Some(AnsiSequence::PrimaryAttributes(class, features)) => Ok(Some((class, features))),
+            _ => Ok(None),
+        }
+    }
+
+    ///The terminal type, firmware version and ROM cartridge number, through DA2.
+    pub fn secondary_attributes(&mut self) -> io::Result<Option<(u32, u32, u32)>> {
+        let reply = self.query(&AnsiSequence::RequestSecondaryAttributes, |seq| {
+            matches!(seq, AnsiSequence::SecondaryAttributes(..))
+        })?;
+        match reply {
+            Some(AnsiSequence::SecondaryAttributes(kind, version, rom)) => {
+                Ok(Some((kind, version, rom)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    ///The name and version of the terminal, through XTVERSION.
+    pub fn terminal_version(&mut self) -> io::Result<Option<String>> {
+        let reply = self.query(&AnsiSequence::RequestTerminalVersion, |seq| {
+            matches!(seq, AnsiSequence::TerminalVersion(..))
+        })?;
+        match reply {
+            Some(AnsiSequence::TerminalVersion(version, _)) => Ok(Some(version.to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    ///The current foreground, background or cursor color, through OSC 10, 11 or 12.
+    pub fn dynamic_color(&mut self, which: DynamicColor) -> io::Result<Option<RgbColor>> {
+        let mut specs = heapless::Vec::new();
+        //Can't fail, the list is empty.
+        let _ = specs.push(ColorSpec::Query);
+        let query = AnsiSequence::DynamicColor(which, specs, Terminator::St);
+        let reply = self.query(&query, |seq| match seq {
+            AnsiSequence::DynamicColor(color, specs, _) if *color == which => {
+                matches!(specs.first(), Some(ColorSpec::Rgb(_)))
+            }
+            _ => false,
+        })?;
+        match reply {
+            Some(AnsiSequence::DynamicColor(_, specs, _)) => Ok(specs[0].rgb()),
+            _ => Ok(None),
+        }
+    }
+}
+
+//Looks for the DA1 reply that ends the answers to a query, along with the first reply before
+//it that `is_reply` picks out. A DA1 reply can be both.
+fn find<F>(buf: &[u8], is_reply: &mut F) -> Option<Found>
+where
+    F: FnMut(&AnsiSequence<'_>) -> bool,
+{
+    let mut reply = None;
+    let mut iter = buf.ansi_parse();
+    loop {
+        let start = buf.len() - iter.dat.len();
+        let out = iter.next()?;
+        let range = start..buf.len() - iter.dat.len();
+
+        //Any `CSI ? ... c` ends the answers, even one whose codes couldn't be read.
+        let is_fence = match out {
+            ByteOutput::Escape(seq) => {
+                if reply.is_none() && is_reply(&seq) {
+                    reply = Some(range.clone());
+                }
+                matches!(seq, AnsiSequence::PrimaryAttributes(..))
+            }
+            ByteOutput::Unknown(Sequence::Csi(csi)) => {
+                csi.private_marker == Some('?') && csi.final_byte == 'c'
+            }
+            _ => false,
+        };
+        if is_fence {
+            return Some(Found {
+                reply,
+                fence: range,
+            });
+        }
+    }
+}
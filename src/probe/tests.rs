@@ -0,0 +1,119 @@
+use crate::*;
+
+use std::io::{self, Read};
+
+//Hands out its data a byte at a time, like a slow terminal.
+struct Trickle<'a>(&'a [u8]);
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.split_first() {
+            Some((&byte, rest)) if !buf.is_empty() => {
+                buf[0] = byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn test_cursor_position() {
+    let input = &b"ls\x1b[24;80R\x1b[A\x1b[?62;22ctail"[..];
+    let mut probe = TerminalProbe::new(input, Vec::new());
+    assert_eq!(probe.cursor_position().unwrap(), Some((24, 80)));
+    assert_eq!(probe.take_unrelated(), b"ls\x1b[Atail");
+    assert_eq!(probe.take_unrelated(), b"");
+
+    let (_, written) = probe.into_inner();
+    assert_eq!(written, b"\x1b[6n\x1b[c");
+}
+
+#[test]
+fn test_unanswered() {
+    let mut probe = TerminalProbe::new(&b"\x1b[?1;2c"[..], Vec::new());
+    assert_eq!(probe.mode(DecMode::SynchronizedOutput).unwrap(), None);
+    assert_eq!(probe.take_unrelated(), b"");
+
+    //The fence never came.
+    let mut probe = TerminalProbe::new(&b"\x1b[?2026;2$y"[..], Vec::new());
+    let err = probe.mode(DecMode::SynchronizedOutput).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_split_reads() {
+    let input = b"\x1b[?2004;1$y\x1b[?2026;2$y\x1b[?62c\x1bP>|foot(1.16.2)\x1b\\\x1b[?62c";
+    let mut probe = TerminalProbe::new(Trickle(input), Vec::new());
+    assert_eq!(
+        probe.mode(DecMode::SynchronizedOutput).unwrap(),
+        Some(ModeState::Reset)
+    );
+    //A report for another mode isn't the answer.
+    assert_eq!(probe.take_unrelated(), b"\x1b[?2004;1$y");
+
+    assert_eq!(
+        probe.terminal_version().unwrap().as_deref(),
+        Some("foot(1.16.2)")
+    );
+    assert_eq!(probe.take_unrelated(), b"");
+}
+
+#[test]
+fn test_attributes() {
+    let input = &b"\x1b[?65;4;22c\x1b[>1;4000;0c\x1b[?65;4;22c"[..];
+    let mut probe = TerminalProbe::new(input, Vec::new());
+
+    let (class, features) = probe.primary_attributes().unwrap().unwrap();
+    assert_eq!(class, 65);
+    assert_eq!(
+        features[..],
+        [DeviceAttribute::Sixel, DeviceAttribute::AnsiColor]
+    );
+    assert_eq!(probe.secondary_attributes().unwrap(), Some((1, 4000, 0)));
+    assert_eq!(probe.take_unrelated(), b"");
+
+    let (_, written) = probe.into_inner();
+    assert_eq!(written, b"\x1b[c\x1b[>c\x1b[c");
+}
+
+#[test]
+fn test_dynamic_color() {
+    let input = &b"\x1b]11;rgb:1e1e/1e1e/2e2e\x07\x1b[?62c"[..];
+    let mut probe = TerminalProbe::new(input, Vec::new());
+    let color = probe
+        .dynamic_color(DynamicColor::Background)
+        .unwrap()
+        .unwrap();
+    assert_eq!(color.blue.value(), 0x2e2e);
+
+    let (_, written) = probe.into_inner();
+    assert_eq!(written, b"\x1b]11;?\x1b\\\x1b[c");
+}
+
+#[test]
+fn test_odd_fences() {
+    //kitty ends its DA1 reply with an empty code.
+    let input = &b"\x1b[1;1R\x1b[?62;c"[..];
+    let mut probe = TerminalProbe::new(Trickle(input), Vec::new());
+    assert_eq!(probe.cursor_position().unwrap(), Some((1, 1)));
+    assert_eq!(probe.take_unrelated(), b"");
+
+    //A DA1 reply whose codes can't be read still ends the answers.
+    let input = &b"\x1b[?99999;1c\x1b[3;7R\x1b[?99999;1c"[..];
+    let mut probe = TerminalProbe::new(Trickle(input), Vec::new());
+    assert_eq!(probe.primary_attributes().unwrap(), None);
+    assert_eq!(probe.cursor_position().unwrap(), Some((3, 7)));
+    assert_eq!(probe.take_unrelated(), b"");
+}
+
+#[test]
+fn test_long_primary_attributes() {
+    let input = &b"\x1b[?62;1;2;6;9;15;16;17;18;21;22;28;29;52;4;8;7c"[..];
+    let mut probe = TerminalProbe::new(Trickle(input), Vec::new());
+    let (class, features) = probe.primary_attributes().unwrap().unwrap();
+    assert_eq!(class, 62);
+    assert_eq!(features.len(), 16);
+    assert_eq!(features[0], DeviceAttribute::Columns132);
+}
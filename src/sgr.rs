@@ -0,0 +1,403 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{AnsiSequence, SgrParam};
+
+use heapless::{consts::U64, Vec};
+
+///The eight standard colors, plus their bright variants.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NamedColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl NamedColor {
+    fn from_index(idx: u8) -> NamedColor {
+        use NamedColor::*;
+        match idx {
+            0 => Black,
+            1 => Red,
+            2 => Green,
+            3 => Yellow,
+            4 => Blue,
+            5 => Magenta,
+            6 => Cyan,
+            7 => White,
+            8 => BrightBlack,
+            9 => BrightRed,
+            10 => BrightGreen,
+            11 => BrightYellow,
+            12 => BrightBlue,
+            13 => BrightMagenta,
+            14 => BrightCyan,
+            _ => BrightWhite,
+        }
+    }
+
+    ///The position of this color in the 16 color palette.
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+///A color as set by an SGR sequence.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Color {
+    #[default]
+    Default,
+    Named(NamedColor),
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+///Text attributes that can be switched on and off through SGR.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Attribute {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    DoubleUnderline,
+    CurlyUnderline,
+    DottedUnderline,
+    DashedUnderline,
+    SlowBlink,
+    RapidBlink,
+    Reverse,
+    Hidden,
+    Strike,
+    Overline,
+}
+
+impl Attribute {
+    //The styled underlines have no code of their own, they are written as `4:<style>`.
+    fn set_code(self) -> u8 {
+        use Attribute::*;
+        match self {
+            Bold => 1,
+            Dim => 2,
+            Italic => 3,
+            Underline | CurlyUnderline | DottedUnderline | DashedUnderline => 4,
+            DoubleUnderline => 21,
+            SlowBlink => 5,
+            RapidBlink => 6,
+            Reverse => 7,
+            Hidden => 8,
+            Strike => 9,
+            Overline => 53,
+        }
+    }
+
+    fn unset_code(self) -> u8 {
+        use Attribute::*;
+        match self {
+            Bold | Dim => 22,
+            Italic => 23,
+            Underline | DoubleUnderline | CurlyUnderline | DottedUnderline | DashedUnderline => 24,
+            SlowBlink | RapidBlink => 25,
+            Reverse => 27,
+            Hidden => 28,
+            Strike => 29,
+            Overline => 55,
+        }
+    }
+
+    //Attributes that are cleared by the same SGR code as this one.
+    fn family(self) -> &'static [Attribute] {
+        use Attribute::*;
+        match self {
+            Bold | Dim => &[Bold, Dim],
+            Underline | DoubleUnderline | CurlyUnderline | DottedUnderline | DashedUnderline => &[
+                Underline,
+                DoubleUnderline,
+                CurlyUnderline,
+                DottedUnderline,
+                DashedUnderline,
+            ],
+            SlowBlink | RapidBlink => &[SlowBlink, RapidBlink],
+            Italic => &[Italic],
+            Reverse => &[Reverse],
+            Hidden => &[Hidden],
+            Strike => &[Strike],
+            Overline => &[Overline],
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << (self as u16)
+    }
+}
+
+///A single decoded action from a `SetGraphicsMode` sequence.
+///
+///Some attributes share the code that turns them off: unsetting `Bold` or `Dim` both emit 22,
+///all of the underline styles share 24 and the blink variants share 25. When decoding, these
+///codes come back as `Unset(Bold)`, `Unset(Underline)` and `Unset(SlowBlink)` respectively.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SgrAction {
+    Reset,
+    Set(Attribute),
+    Unset(Attribute),
+    Foreground(Color),
+    Background(Color),
+    UnderlineColor(Color),
+    Unknown(u8),
+    ///A code with sub-parameters that aren't understood, such as `4:9`. Only the code is kept,
+    ///so this can't be written back out.
+    UnknownSubParams(u8),
+}
+
+///The accumulated text style after applying a series of `SgrAction`s.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Style {
+    pub foreground: Color,
+    pub background: Color,
+    pub underline_color: Color,
+    attributes: u16,
+}
+
+impl Style {
+    pub fn has_attribute(&self, attr: Attribute) -> bool {
+        self.attributes & attr.bit() != 0
+    }
+
+    fn clear_family(&mut self, attr: Attribute) {
+        for attr in attr.family() {
+            self.attributes &= !attr.bit();
+        }
+    }
+
+    pub fn apply(&mut self, action: SgrAction) {
+        use SgrAction::*;
+        match action {
+            Reset => *self = Style::default(),
+            Set(attr) => {
+                //Text has only one underline style at a time.
+                if attr.family().contains(&Attribute::Underline) {
+                    self.clear_family(attr);
+                }
+                self.attributes |= attr.bit();
+            }
+            Unset(attr) => self.clear_family(attr),
+            Foreground(color) => self.foreground = color,
+            Background(color) => self.background = color,
+            UnderlineColor(color) => self.underline_color = color,
+            Unknown(_) | UnknownSubParams(_) => {}
+        }
+    }
+
+    ///Applies every action in `seq` if it is a `SetGraphicsMode`, otherwise does nothing.
+    pub fn apply_sequence(&mut self, seq: &AnsiSequence<'_>) {
+        if let Some(actions) = seq.sgr_actions() {
+            for action in actions {
+                self.apply(action);
+            }
+        }
+    }
+}
+
+//Reads the color following a 38, 48 or 58 in its `;` form, returning it along with the number
+//of parameters used. `None` means the parameters ran out before the color was complete.
+fn extended_color(params: &[SgrParam]) -> Option<(Color, usize)> {
+    let val = |i: usize| params.get(i).map(|p| p.value());
+    match val(0)? {
+        5 => Some((Color::Indexed(val(1)?), 2)),
+        2 => Some((Color::Rgb(val(1)?, val(2)?, val(3)?), 4)),
+        _ => None,
+    }
+}
+
+//Reads the sub-parameters of a 38, 48 or 58 in its `:` form. The color space id in
+//`38:2:<id>:r:g:b` is optional, and ignored when present.
+fn sub_color(subs: &[SgrParam]) -> Option<Color> {
+    let val = |i: usize| subs.get(i).map(|p| p.value());
+    match (val(0)?, subs.len()) {
+        (5, 2) => Some(Color::Indexed(val(1)?)),
+        (2, 4) => Some(Color::Rgb(val(1)?, val(2)?, val(3)?)),
+        (2, 5) => Some(Color::Rgb(val(2)?, val(3)?, val(4)?)),
+        _ => None,
+    }
+}
+
+fn decode_sub(code: u8, subs: &[SgrParam]) -> SgrAction {
+    use SgrAction::*;
+    match (code, subs[0].value()) {
+        (4, 0) if subs.len() == 1 => Unset(Attribute::Underline),
+        (4, 1) if subs.len() == 1 => Set(Attribute::Underline),
+        (4, 2) if subs.len() == 1 => Set(Attribute::DoubleUnderline),
+        (4, 3) if subs.len() == 1 => Set(Attribute::CurlyUnderline),
+        (4, 4) if subs.len() == 1 => Set(Attribute::DottedUnderline),
+        (4, 5) if subs.len() == 1 => Set(Attribute::DashedUnderline),
+        (38, _) => sub_color(subs).map_or(UnknownSubParams(code), Foreground),
+        (48, _) => sub_color(subs).map_or(UnknownSubParams(code), Background),
+        (58, _) => sub_color(subs).map_or(UnknownSubParams(code), UnderlineColor),
+        _ => UnknownSubParams(code),
+    }
+}
+
+fn decode(params: &[SgrParam]) -> Vec<SgrAction, U64> {
+    use SgrAction::*;
+
+    let mut actions = Vec::new();
+    if params.is_empty() {
+        let _ = actions.push(Reset);
+        return actions;
+    }
+
+    let mut i = 0;
+    while i < params.len() {
+        let code = params[i].value();
+        i += 1;
+
+        let subs = params[i..]
+            .iter()
+            .take_while(|p| matches!(p, SgrParam::SubParam(_)))
+            .count();
+        if subs > 0 {
+            let _ = actions.push(decode_sub(code, &params[i..i + subs]));
+            i += subs;
+            continue;
+        }
+
+        let action = match code {
+            0 => Reset,
+            1 => Set(Attribute::Bold),
+            2 => Set(Attribute::Dim),
+            3 => Set(Attribute::Italic),
+            4 => Set(Attribute::Underline),
+            5 => Set(Attribute::SlowBlink),
+            6 => Set(Attribute::RapidBlink),
+            7 => Set(Attribute::Reverse),
+            8 => Set(Attribute::Hidden),
+            9 => Set(Attribute::Strike),
+            21 => Set(Attribute::DoubleUnderline),
+            22 => Unset(Attribute::Bold),
+            23 => Unset(Attribute::Italic),
+            24 => Unset(Attribute::Underline),
+            25 => Unset(Attribute::SlowBlink),
+            27 => Unset(Attribute::Reverse),
+            28 => Unset(Attribute::Hidden),
+            29 => Unset(Attribute::Strike),
+            30..=37 => Foreground(Color::Named(NamedColor::from_index(code - 30))),
+            39 => Foreground(Color::Default),
+            40..=47 => Background(Color::Named(NamedColor::from_index(code - 40))),
+            49 => Background(Color::Default),
+            53 => Set(Attribute::Overline),
+            55 => Unset(Attribute::Overline),
+            59 => UnderlineColor(Color::Default),
+            90..=97 => Foreground(Color::Named(NamedColor::from_index(code - 90 + 8))),
+            100..=107 => Background(Color::Named(NamedColor::from_index(code - 100 + 8))),
+            38 | 48 | 58 => match extended_color(&params[i..]) {
+                Some((color, used)) => {
+                    i += used;
+                    match code {
+                        38 => Foreground(color),
+                        48 => Background(color),
+                        _ => UnderlineColor(color),
+                    }
+                }
+                None => {
+                    //A truncated color spec swallows the rest of the sequence, otherwise
+                    //its leftover parameters would be misread as attributes.
+                    i = params.len();
+                    Unknown(code)
+                }
+            },
+            _ => Unknown(code),
+        };
+
+        //There is never more than one action per parameter, so this cannot overflow.
+        let _ = actions.push(action);
+    }
+
+    actions
+}
+
+fn push_all(params: &mut Vec<SgrParam, U64>, vals: &[u8]) -> Result<(), SgrParam> {
+    for val in vals {
+        params.push(SgrParam::from(*val))?;
+    }
+    Ok(())
+}
+
+fn push_color(params: &mut Vec<SgrParam, U64>, base: u8, color: Color) -> Result<(), SgrParam> {
+    match color {
+        Color::Default => push_all(params, &[base + 1]),
+        Color::Named(named) => {
+            let idx = named.index();
+            match base {
+                38 if idx < 8 => push_all(params, &[30 + idx]),
+                38 => push_all(params, &[90 + idx - 8]),
+                48 if idx < 8 => push_all(params, &[40 + idx]),
+                48 => push_all(params, &[100 + idx - 8]),
+                //There is no short form for underline colors.
+                _ => push_all(params, &[base, 5, idx]),
+            }
+        }
+        Color::Indexed(idx) => push_all(params, &[base, 5, idx]),
+        Color::Rgb(r, g, b) => push_all(params, &[base, 2, r, g, b]),
+    }
+}
+
+fn push_attribute(params: &mut Vec<SgrParam, U64>, attr: Attribute) -> Result<(), SgrParam> {
+    use Attribute::*;
+    let style = match attr {
+        CurlyUnderline => 3,
+        DottedUnderline => 4,
+        DashedUnderline => 5,
+        _ => return push_all(params, &[attr.set_code()]),
+    };
+
+    push_all(params, &[4])?;
+    params.push(SgrParam::SubParam(Some(style)))
+}
+
+impl<'a> AnsiSequence<'a> {
+    ///Decodes a `SetGraphicsMode` into its typed actions. Returns `None` for any other
+    ///sequence.
+    pub fn sgr_actions(&self) -> Option<Vec<SgrAction, U64>> {
+        match self {
+            AnsiSequence::SetGraphicsMode(params) => Some(decode(params)),
+            _ => None,
+        }
+    }
+
+    ///Builds a `SetGraphicsMode` out of typed actions. Returns `None` if the actions need more
+    ///parameters than a `SetGraphicsMode` can hold, or if one of them is an
+    ///`UnknownSubParams`.
+    pub fn from_sgr_actions(actions: &[SgrAction]) -> Option<AnsiSequence<'a>> {
+        use SgrAction::*;
+
+        let mut params = Vec::new();
+        for action in actions {
+            match *action {
+                Reset => push_all(&mut params, &[0]),
+                Set(attr) => push_attribute(&mut params, attr),
+                Unset(attr) => push_all(&mut params, &[attr.unset_code()]),
+                Foreground(color) => push_color(&mut params, 38, color),
+                Background(color) => push_color(&mut params, 48, color),
+                UnderlineColor(color) => push_color(&mut params, 58, color),
+                Unknown(code) => push_all(&mut params, &[code]),
+                UnknownSubParams(_) => return None,
+            }
+            .ok()?;
+        }
+
+        Some(AnsiSequence::SetGraphicsMode(params))
+    }
+}
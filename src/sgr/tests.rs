@@ -0,0 +1,152 @@
+use super::*;
+
+use crate::parsers::parse_escape;
+
+fn actions(seq: &str) -> std::vec::Vec<SgrAction> {
+    let seq = parse_escape(seq).expect("failed to parse").1;
+    seq.sgr_actions().expect("not an SGR").into_iter().collect()
+}
+
+#[test]
+fn test_decode_basic() {
+    assert_eq!(actions("\x1b[m"), vec![SgrAction::Reset]);
+    assert_eq!(
+        actions("\x1b[0;1;31;42m"),
+        vec![
+            SgrAction::Reset,
+            SgrAction::Set(Attribute::Bold),
+            SgrAction::Foreground(Color::Named(NamedColor::Red)),
+            SgrAction::Background(Color::Named(NamedColor::Green)),
+        ]
+    );
+    assert_eq!(
+        actions("\x1b[97;104;39;49m"),
+        vec![
+            SgrAction::Foreground(Color::Named(NamedColor::BrightWhite)),
+            SgrAction::Background(Color::Named(NamedColor::BrightBlue)),
+            SgrAction::Foreground(Color::Default),
+            SgrAction::Background(Color::Default),
+        ]
+    );
+}
+
+#[test]
+fn test_decode_extended_colors() {
+    assert_eq!(
+        actions("\x1b[38;2;255;128;0;48;5;236;58;5;1m"),
+        vec![
+            SgrAction::Foreground(Color::Rgb(255, 128, 0)),
+            SgrAction::Background(Color::Indexed(236)),
+            SgrAction::UnderlineColor(Color::Indexed(1)),
+        ]
+    );
+    assert_eq!(
+        actions("\x1b[1;38;5m"),
+        vec![SgrAction::Set(Attribute::Bold), SgrAction::Unknown(38)]
+    );
+}
+
+#[test]
+fn test_not_sgr() {
+    assert_eq!(AnsiSequence::CursorUp(1).sgr_actions(), None);
+}
+
+#[test]
+fn test_encode_round_trip() {
+    let acts = [
+        SgrAction::Reset,
+        SgrAction::Set(Attribute::Italic),
+        SgrAction::Set(Attribute::Overline),
+        SgrAction::Unset(Attribute::Dim),
+        SgrAction::Foreground(Color::Named(NamedColor::BrightRed)),
+        SgrAction::Background(Color::Rgb(1, 2, 3)),
+        SgrAction::UnderlineColor(Color::Default),
+    ];
+
+    let seq = AnsiSequence::from_sgr_actions(&acts).unwrap();
+    assert_eq!(format!("{}", seq), "\x1b[0;3;53;22;91;48;2;1;2;3;59m");
+
+    let mut expected = acts.to_vec();
+    expected[3] = SgrAction::Unset(Attribute::Bold);
+    assert_eq!(actions(&format!("{}", seq)), expected);
+}
+
+#[test]
+fn test_encode_overflow() {
+    let acts = [SgrAction::Foreground(Color::Rgb(1, 2, 3)); 13];
+    assert_eq!(AnsiSequence::from_sgr_actions(&acts), None);
+}
+
+#[test]
+fn test_style() {
+    let mut style = Style::default();
+    for seq in &["\x1b[1;2;4;31m", "\x1b[22;48;5;17m"] {
+        style.apply_sequence(&parse_escape(seq).unwrap().1);
+    }
+
+    assert!(!style.has_attribute(Attribute::Bold));
+    assert!(!style.has_attribute(Attribute::Dim));
+    assert!(style.has_attribute(Attribute::Underline));
+    assert_eq!(style.foreground, Color::Named(NamedColor::Red));
+    assert_eq!(style.background, Color::Indexed(17));
+
+    style.apply(SgrAction::Reset);
+    assert_eq!(style, Style::default());
+}
+
+#[test]
+fn test_decode_sub_params() {
+    assert_eq!(
+        actions("\x1b[4:3;58:2::255:0:0;38:2:1:2:3;48:5:17m"),
+        vec![
+            SgrAction::Set(Attribute::CurlyUnderline),
+            SgrAction::UnderlineColor(Color::Rgb(255, 0, 0)),
+            SgrAction::Foreground(Color::Rgb(1, 2, 3)),
+            SgrAction::Background(Color::Indexed(17)),
+        ]
+    );
+    assert_eq!(
+        actions("\x1b[4:0;4:9;38:7m"),
+        vec![
+            SgrAction::Unset(Attribute::Underline),
+            SgrAction::UnknownSubParams(4),
+            SgrAction::UnknownSubParams(38),
+        ]
+    );
+
+    //Writing `4:9` back as `4` would turn it into a plain underline.
+    let acts = actions("\x1b[1;4:9m");
+    assert_eq!(AnsiSequence::from_sgr_actions(&acts), None);
+    assert!(AnsiSequence::from_sgr_actions(&acts[..1]).is_some());
+}
+
+#[test]
+fn test_encode_underline_style() {
+    let seq = AnsiSequence::from_sgr_actions(&[
+        SgrAction::Set(Attribute::DottedUnderline),
+        SgrAction::Set(Attribute::Bold),
+    ])
+    .unwrap();
+    assert_eq!(format!("{}", seq), "\x1b[4:4;1m");
+
+    let mut style = Style::default();
+    style.apply_sequence(&seq);
+    assert!(style.has_attribute(Attribute::DottedUnderline));
+    style.apply(SgrAction::Unset(Attribute::Underline));
+    assert!(!style.has_attribute(Attribute::DottedUnderline));
+}
+
+#[test]
+fn test_underline_styles_exclusive() {
+    let mut style = Style::default();
+    for seq in &["\x1b[4:1;1m", "\x1b[4:3m"] {
+        style.apply_sequence(&parse_escape(seq).unwrap().1);
+    }
+    assert!(style.has_attribute(Attribute::CurlyUnderline));
+    assert!(!style.has_attribute(Attribute::Underline));
+    assert!(style.has_attribute(Attribute::Bold));
+
+    style.apply_sequence(&parse_escape("\x1b[21m").unwrap().1);
+    assert!(style.has_attribute(Attribute::DoubleUnderline));
+    assert!(!style.has_attribute(Attribute::CurlyUnderline));
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{AnsiParseIterator, AnsiSequence, Output};
+
+use core::fmt::{Display, Formatter, Result as DisplayResult};
+
+///The FinalTerm semantic prompt marks, which shells send around their prompt, the command line
+///and the command's output.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MarkKind {
+    ///`A`
+    PromptStart,
+    ///`B`, the end of the prompt and the start of the command line.
+    CommandStart,
+    ///`C`, the end of the command line and the start of the command's output.
+    CommandExecuted,
+    ///`D`, optionally followed by the exit code.
+    CommandFinished,
+}
+
+impl MarkKind {
+    pub(crate) fn from_char(ch: char) -> MarkKind {
+        match ch {
+            'A' => MarkKind::PromptStart,
+            'B' => MarkKind::CommandStart,
+            'C' => MarkKind::CommandExecuted,
+            _ => MarkKind::CommandFinished,
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            MarkKind::PromptStart => 'A',
+            MarkKind::CommandStart => 'B',
+            MarkKind::CommandExecuted => 'C',
+            MarkKind::CommandFinished => 'D',
+        }
+    }
+}
+
+///A semantic prompt mark, as sent through OSC 133 and OSC 633.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ShellMark<'a> {
+    pub kind: MarkKind,
+    ///Everything after the `;` following the mark, if there was one. For `CommandFinished`
+    ///this starts with the exit code, and is otherwise made up of `key=value` options.
+    pub params: Option<&'a str>,
+}
+
+impl<'a> ShellMark<'a> {
+    pub fn new(kind: MarkKind) -> ShellMark<'a> {
+        ShellMark { kind, params: None }
+    }
+
+    ///The exit code of a `CommandFinished` mark.
+    pub fn exit_code(&self) -> Option<i32> {
+        match (self.kind, self.params) {
+            (MarkKind::CommandFinished, Some(params)) => {
+                params.split(';').next().and_then(|code| code.parse().ok())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Display for ShellMark<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "{}", self.kind.as_char())?;
+        match self.params {
+            Some(params) => write!(formatter, ";{}", params),
+            None => Ok(()),
+        }
+    }
+}
+
+///The contents of an OSC 633 sequence, VS Code's take on shell integration.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VsCodeMark<'a> {
+    ///`A` to `D`, which work as they do for OSC 133.
+    Mark(ShellMark<'a>),
+    ///`E`, the command line about to be run. It is escaped, and may be followed by a nonce.
+    CommandLine(&'a str),
+    ///`P`, a `key=value` property such as `Cwd`.
+    Property(&'a str, &'a str),
+}
+
+impl<'a> Display for VsCodeMark<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        match self {
+            VsCodeMark::Mark(mark) => write!(formatter, "{}", mark),
+            VsCodeMark::CommandLine(cmd) => write!(formatter, "E;{}", cmd),
+            VsCodeMark::Property(key, val) => write!(formatter, "P;{}={}", key, val),
+        }
+    }
+}
+
+///The working directory reported through OSC 7 as a `file://` URL. The path is left
+///percent-encoded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FileUrl<'a> {
+    pub host: &'a str,
+    ///Starts with a `/`.
+    pub path: &'a str,
+}
+
+impl<'a> Display for FileUrl<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> DisplayResult {
+        write!(formatter, "file://{}{}", self.host, self.path)
+    }
+}
+
+///A part of a shell session, as delimited by semantic prompt marks. The text is the input
+///as is between two marks, escape sequences included.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Segment<'a> {
+    ///Anything outside of a prompt and a command, such as what comes before the first mark.
+    Other(&'a str),
+    Prompt(&'a str),
+    ///The command line, as typed in by the user.
+    Command(&'a str),
+    ///The output of a command. The exit code is missing if the shell didn't report one, or if
+    ///the next prompt started before the command finished.
+    CommandOutput {
+        text: &'a str,
+        exit_code: Option<i32>,
+    },
+}
+
+fn shell_mark<'a>(output: &Output<'a>) -> Option<ShellMark<'a>> {
+    match output {
+        Output::Escape(AnsiSequence::SemanticPrompt(mark, _))
+        | Output::Escape(AnsiSequence::VsCodeShellIntegration(VsCodeMark::Mark(mark), _)) => {
+            Some(*mark)
+        }
+        _ => None,
+    }
+}
+
+impl<'a> AnsiParseIterator<'a> {
+    ///Groups the rest of the input into prompts, commands and their output, using the
+    ///semantic prompt marks sent by the shell.
+    pub fn segments(self) -> SegmentIterator<'a> {
+        SegmentIterator {
+            start: self.dat,
+            state: MarkKind::CommandFinished,
+            inner: self,
+        }
+    }
+}
+
+///Splits a shell session into `Segment`s. Created through `AnsiParseIterator::segments`.
+#[derive(Debug)]
+pub struct SegmentIterator<'a> {
+    inner: AnsiParseIterator<'a>,
+    //Where the current segment starts.
+    start: &'a str,
+    //The last mark seen.
+    state: MarkKind,
+}
+
+impl<'a> SegmentIterator<'a> {
+    fn segment(&self, text: &'a str, exit_code: Option<i32>) -> Option<Segment<'a>> {
+        match self.state {
+            MarkKind::PromptStart => Some(Segment::Prompt(text)),
+            MarkKind::CommandStart => Some(Segment::Command(text)),
+            MarkKind::CommandExecuted => Some(Segment::CommandOutput { text, exit_code }),
+            MarkKind::CommandFinished if text.is_empty() => None,
+            MarkKind::CommandFinished => Some(Segment::Other(text)),
+        }
+    }
+}
+
+impl<'a> Iterator for SegmentIterator<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let before = self.inner.dat;
+            let output = match self.inner.next() {
+                Some(output) => output,
+                None => {
+                    let text = self.start;
+                    self.start = "";
+                    let segment = self.segment(text, None);
+                    self.state = MarkKind::CommandFinished;
+                    return segment;
+                }
+            };
+
+            if let Some(mark) = shell_mark(&output) {
+                let text = &self.start[..self.start.len() - before.len()];
+                let segment = self.segment(text, mark.exit_code());
+                self.start = self.inner.dat;
+                self.state = mark.kind;
+                if segment.is_some() {
+                    return segment;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::*;
+
+#[test]
+fn test_exit_code() {
+    let finished = |params| ShellMark {
+        kind: MarkKind::CommandFinished,
+        params,
+    };
+    assert_eq!(finished(None).exit_code(), None);
+    assert_eq!(finished(Some("0")).exit_code(), Some(0));
+    assert_eq!(finished(Some("130;aid=1")).exit_code(), Some(130));
+    assert_eq!(finished(Some("aid=1")).exit_code(), None);
+    assert_eq!(
+        ShellMark {
+            kind: MarkKind::PromptStart,
+            params: Some("1"),
+        }
+        .exit_code(),
+        None
+    );
+}
+
+#[test]
+fn test_segments() {
+    let session = "motd\n\
+        \x1b]133;A\x07$ \x1b]133;B\x07ls\n\x1b]133;C\x07\x1b[1mfile\x1b[0m\n\x1b]133;D;0\x07\
+        \x1b]133;A\x07$ \x1b]133;B\x07false\n\x1b]133;C\x07\x1b]133;D;1\x07\
+        \x1b]633;A\x07$ \x1b]633;B\x07";
+
+    let segments: Vec<_> = session.ansi_parse().segments().collect();
+    assert_eq!(
+        segments,
+        vec![
+            Segment::Other("motd\n"),
+            Segment::Prompt("$ "),
+            Segment::Command("ls\n"),
+            Segment::CommandOutput {
+                text: "\x1b[1mfile\x1b[0m\n",
+                exit_code: Some(0)
+            },
+            Segment::Prompt("$ "),
+            Segment::Command("false\n"),
+            Segment::CommandOutput {
+                text: "",
+                exit_code: Some(1)
+            },
+            Segment::Prompt("$ "),
+            Segment::Command(""),
+        ]
+    );
+}
+
+#[test]
+fn test_segments_unfinished() {
+    let session = "\x1b]133;C\x07out\x1b]133;A\x07$ ";
+    let segments: Vec<_> = session.ansi_parse().segments().collect();
+    assert_eq!(
+        segments,
+        vec![
+            Segment::CommandOutput {
+                text: "out",
+                exit_code: None
+            },
+            Segment::Prompt("$ "),
+        ]
+    );
+
+    assert_eq!(
+        "plain".ansi_parse().segments().collect::<Vec<_>>(),
+        vec![Segment::Other("plain")]
+    );
+    assert_eq!("".ansi_parse().segments().next(), None);
+}
@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod tests;
+
+use crate::Dcs;
+
+use std::vec::Vec;
+
+//Images are refused beyond this many pixels, as the size is up to whoever wrote the sixels.
+const MAX_PIXELS: usize = 1 << 24;
+
+const REGISTERS: usize = 256;
+
+//The VT340 palette, in percent.
+const DEFAULT_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (20, 20, 80),
+    (80, 13, 13),
+    (20, 80, 20),
+    (80, 20, 80),
+    (20, 80, 80),
+    (80, 80, 20),
+    (53, 53, 53),
+    (26, 26, 26),
+    (33, 33, 60),
+    (60, 26, 26),
+    (33, 60, 33),
+    (60, 33, 60),
+    (33, 60, 60),
+    (60, 60, 33),
+    (80, 80, 80),
+];
+
+fn percent(val: usize) -> u8 {
+    ((val.min(100) * 255 + 50) / 100) as u8
+}
+
+fn rgb(red: usize, green: usize, blue: usize) -> [u8; 4] {
+    [percent(red), percent(green), percent(blue), 0xff]
+}
+
+//Sixel hues start at blue rather than red, and go round through red and then green.
+fn hls(hue: usize, lightness: usize, saturation: usize) -> [u8; 4] {
+    let hue = ((hue % 360 + 240) % 360) as f32 / 60.0;
+    let lightness = lightness.min(100) as f32 / 100.0;
+    let saturation = saturation.min(100) as f32 / 100.0;
+
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (red, green, blue) = match hue as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let min = lightness - chroma / 2.0;
+    let channel = |val: f32| ((val + min) * 255.0).round() as u8;
+    [channel(red), channel(green), channel(blue), 0xff]
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Command {
+    Select(usize),
+    Define(usize, [u8; 4]),
+    Raster(usize, usize),
+    //Six pixels, top one in the lowest bit, repeated a number of times.
+    Sixel(u8, usize),
+    CarriageReturn,
+    NextLine,
+}
+
+struct Commands<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Commands<'a> {
+    //Up to five `;` separated numbers, along with how many there were.
+    fn params(&mut self) -> ([usize; 5], usize) {
+        let mut params = [0; 5];
+        let mut count = 0;
+        loop {
+            let mut val: Option<usize> = None;
+            while let Some(digit @ b'0'..=b'9') = self.data.get(self.pos) {
+                let digit = usize::from(digit - b'0');
+                val = Some(val.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                self.pos += 1;
+            }
+            if count < params.len() {
+                params[count] = val.unwrap_or(0);
+            }
+            count += 1;
+            if self.data.get(self.pos) != Some(&b';') {
+                return (params, count);
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+impl<'a> Iterator for Commands<'a> {
+    type Item = Command;
+
+    fn next(&mut self) -> Option<Command> {
+        loop {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            match byte {
+                b'?'..=b'~' => return Some(Command::Sixel(byte - b'?', 1)),
+                b'!' => {
+                    let (params, _) = self.params();
+                    let byte = *self.data.get(self.pos)?;
+                    if let b'?'..=b'~' = byte {
+                        self.pos += 1;
+                        return Some(Command::Sixel(byte - b'?', params[0].max(1)));
+                    }
+                }
+                b'$' => return Some(Command::CarriageReturn),
+                b'-' => return Some(Command::NextLine),
+                b'#' => {
+                    let (params, count) = self.params();
+                    let reg = params[0] % REGISTERS;
+                    let [_, space, x, y, z] = params;
+                    return Some(match (count, space) {
+                        (5, 1) => Command::Define(reg, hls(x, y, z)),
+                        (5, 2) => Command::Define(reg, rgb(x, y, z)),
+                        _ => Command::Select(reg),
+                    });
+                }
+                b'"' => {
+                    let (params, _) = self.params();
+                    return Some(Command::Raster(params[2], params[3]));
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+///A decoded sixel image.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SixelImage {
+    pub width: usize,
+    pub height: usize,
+    ///Four bytes per pixel, red, green, blue and alpha, going row by row from the top left.
+    pub pixels: Vec<u8>,
+}
+
+impl SixelImage {
+    ///Decodes a sixel image, which is sent as a device control string with a final of `q`.
+    ///
+    ///Pixels that no sixel was drawn to are transparent if the second parameter of the string
+    ///is 1, and opaque black otherwise. The pixel aspect ratio is ignored, so every sixel
+    ///pixel ends up as a single pixel in the image.
+    ///
+    ///Fails if the string is not a sixel image, or if the image would be unreasonably large.
+    pub fn decode(dcs: &Dcs<'_>) -> Option<SixelImage> {
+        let header_ok = dcs.private_marker.is_none()
+            && dcs.intermediates.is_empty()
+            && dcs.final_byte == 'q'
+            && dcs.params.bytes().all(|b| b.is_ascii_digit() || b == b';');
+        if !header_ok {
+            return None;
+        }
+        let transparent = dcs.params.split(';').nth(1) == Some("1");
+        let commands = || Commands {
+            data: dcs.data.as_bytes(),
+            pos: 0,
+        };
+
+        let (mut width, mut height) = (0, 0);
+        let (mut x, mut y) = (0usize, 0usize);
+        for command in commands() {
+            match command {
+                Command::Raster(w, h) => {
+                    width = width.max(w);
+                    height = height.max(h);
+                }
+                Command::Sixel(bits, count) => {
+                    x = x.saturating_add(count);
+                    width = width.max(x);
+                    if bits != 0 {
+                        height = height.max(y + 8 - bits.leading_zeros() as usize);
+                    }
+                }
+                Command::CarriageReturn => x = 0,
+                Command::NextLine => {
+                    x = 0;
+                    y = y.saturating_add(6);
+                }
+                _ => (),
+            }
+            //Each side is checked on its own as well, since an image that is zero pixels high
+            //can still run a long way to the right.
+            if width > MAX_PIXELS
+                || height > MAX_PIXELS
+                || width.saturating_mul(height) > MAX_PIXELS
+                || y > MAX_PIXELS
+            {
+                return None;
+            }
+        }
+
+        let mut palette = [[0, 0, 0, 0xff]; REGISTERS];
+        for (reg, &(red, green, blue)) in DEFAULT_PALETTE.iter().enumerate() {
+            palette[reg] = rgb(red.into(), green.into(), blue.into());
+        }
+        let background = if transparent { [0; 4] } else { palette[0] };
+
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for _ in 0..width * height {
+            pixels.extend_from_slice(&background);
+        }
+
+        let (mut x, mut y, mut color) = (0, 0, 0);
+        for command in commands() {
+            match command {
+                Command::Select(reg) => color = reg,
+                Command::Define(reg, rgba) => {
+                    palette[reg] = rgba;
+                    color = reg;
+                }
+                Command::Sixel(bits, count) => {
+                    for row in (0..6).filter(|row| bits & (1 << row) != 0) {
+                        let start = ((y + row) * width + x) * 4;
+                        for pixel in pixels[start..start + count * 4].chunks_mut(4) {
+                            pixel.copy_from_slice(&palette[color]);
+                        }
+                    }
+                    x = x.saturating_add(count);
+                }
+                Command::CarriageReturn => x = 0,
+                Command::NextLine => {
+                    x = 0;
+                    y += 6;
+                }
+                Command::Raster(..) => (),
+            }
+        }
+
+        Some(SixelImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
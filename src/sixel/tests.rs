@@ -0,0 +1,61 @@
+use crate::*;
+
+fn decode(seq: &str) -> Option<SixelImage> {
+    match parse_sequence(seq) {
+        Ok(("", Sequence::Dcs(dcs))) => SixelImage::decode(&dcs),
+        other => panic!("{:?}", other),
+    }
+}
+
+fn pixel(image: &SixelImage, x: usize, y: usize) -> &[u8] {
+    let start = (y * image.width + x) * 4;
+    &image.pixels[start..start + 4]
+}
+
+#[test]
+fn test_decode() {
+    let image = decode("\x1bPq#1;2;100;0;0#1!3~$#2;2;0;100;0@-#1N\x1b\\").unwrap();
+    assert_eq!((image.width, image.height), (3, 10));
+    assert_eq!(image.pixels.len(), 3 * 10 * 4);
+
+    assert_eq!(pixel(&image, 2, 5), [255, 0, 0, 255]);
+    // Only the top pixel of the `@` is set, which is drawn over the red.
+    assert_eq!(pixel(&image, 0, 0), [0, 255, 0, 255]);
+    assert_eq!(pixel(&image, 0, 1), [255, 0, 0, 255]);
+    // `N` is the top four pixels of the next band, which ends the image.
+    assert_eq!(pixel(&image, 0, 6), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 0, 9), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 1, 9), [0, 0, 0, 255]);
+}
+
+#[test]
+fn test_decode_raster_and_transparency() {
+    let image = decode("\x1bP0;1;0q\"1;1;4;2#3~\x1b\\").unwrap();
+    assert_eq!((image.width, image.height), (4, 6));
+    assert_eq!(pixel(&image, 0, 5), [51, 204, 51, 255]);
+    assert_eq!(pixel(&image, 1, 0), [0, 0, 0, 0]);
+
+    let image = decode("\x1bPq\"1;1;2;3\x1b\\").unwrap();
+    assert_eq!((image.width, image.height), (2, 3));
+    assert!(image.pixels.chunks(4).all(|px| px == [0, 0, 0, 255]));
+}
+
+#[test]
+fn test_decode_hls() {
+    let image = decode("\x1bPq#1;1;120;50;100~#2;1;0;50;100~#3;1;0;100;0~\x1b\\").unwrap();
+    assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 1, 0), [0, 0, 255, 255]);
+    assert_eq!(pixel(&image, 2, 0), [255, 255, 255, 255]);
+}
+
+#[test]
+fn test_decode_invalid() {
+    assert_eq!(decode("\x1bP$qm\x1b\\"), None);
+    assert_eq!(decode("\x1bPq!99999999~-!99999999~\x1b\\"), None);
+    assert_eq!(decode("\x1bPq\"1;1;100000;100000\x1b\\"), None);
+    assert_eq!(decode("\x1bPq!99999999999999999999999?!5?\x1b\\"), None);
+    assert_eq!(decode("\x1bPq\"1;1;100000000;0\x1b\\"), None);
+
+    let image = decode("\x1bPq\x1b\\").unwrap();
+    assert_eq!((image.width, image.height), (0, 0));
+}
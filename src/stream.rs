@@ -0,0 +1,195 @@
+//heapless 0.6 deprecates its re-export of `ArrayLength`, but it is still the bound that its own
+//containers need.
+#![allow(deprecated)]
+
+#[cfg(test)]
+mod tests;
+
+use crate::enums::ByteOutput;
+use crate::parsers::{is_incomplete_bytes, parse_bytes, utf8_width};
+use crate::traits::{find_introducer, AnsiByteParseIterator, AnsiByteParser};
+
+use core::mem;
+use heapless::{consts::U8192, ArrayLength, Vec};
+
+//Shrinks by popping, as the heapless 0.6 containers index past the end of their buffer while
+//truncating, which debug builds catch as undefined behavior.
+fn truncate<N: ArrayLength<u8>>(buf: &mut Vec<u8, N>, len: usize) {
+    while buf.len() > len {
+        buf.pop();
+    }
+}
+
+//Where a multi-byte character that was cut off at the end of `chunk` starts, or the length of
+//`chunk` if the last character is whole.
+fn split_char_start(chunk: &[u8]) -> usize {
+    let len = chunk.len();
+    for start in (len.saturating_sub(3)..len).rev() {
+        let byte = chunk[start];
+        if (0x80..=0xbf).contains(&byte) {
+            continue;
+        }
+        return if utf8_width(byte) > len - start {
+            start
+        } else {
+            len
+        };
+    }
+    len
+}
+
+///Parses output that arrives in pieces, such as reads from a PTY. A sequence or a UTF-8
+///character that is cut off at the end of one chunk is held on to, and finished with the start
+///of the next one.
+///
+///Held back bytes are kept in a buffer of `N` bytes. A sequence that doesn't fit is given up
+///on, and passed through as text instead. The default of 8 KiB holds a whole kitty graphics
+///chunk, as kitty keeps their payloads to 4096 bytes. A sixel image or an iTerm2 inline file
+///comes as a single string though, so decoding those needs `N` to be at least as large as the
+///largest image that is expected.
+///
+///A string that is never terminated is held on to until it outgrows the buffer or `finish` is
+///called. This includes one started by an 8-bit C1 DCS or OSC byte, such as a stray `0x90` in
+///output that isn't UTF-8, along with all the text after it. Parsing the same bytes all at
+///once through `AnsiByteParser` passes such a string on as text straight away.
+#[derive(Debug)]
+pub struct AnsiStreamParser<N: ArrayLength<u8> = U8192> {
+    //The sequence or character finished off by the current chunk.
+    ready: Vec<u8, N>,
+    //The start of a sequence or character at the end of the current chunk.
+    partial: Vec<u8, N>,
+}
+
+impl<N: ArrayLength<u8>> AnsiStreamParser<N> {
+    pub fn new() -> Self {
+        AnsiStreamParser {
+            ready: Vec::new(),
+            partial: Vec::new(),
+        }
+    }
+
+    ///Parses the next chunk of input. Anything that is complete is returned right away, the
+    ///rest is kept until the next call to `feed` or `finish`.
+    pub fn feed<'a>(&'a mut self, chunk: &'a [u8]) -> AnsiStreamIterator<'a> {
+        truncate(&mut self.ready, 0);
+        mem::swap(&mut self.ready, &mut self.partial);
+
+        let chunk = if self.ready.is_empty() {
+            chunk
+        } else {
+            self.complete(chunk).unwrap_or_default()
+        };
+        let chunk = self.hold_tail(chunk);
+
+        AnsiStreamIterator {
+            ready: self.ready.ansi_parse(),
+            chunk: chunk.ansi_parse(),
+        }
+    }
+
+    ///Ends the input, returning whatever was held back as text since it can no longer be
+    ///finished.
+    pub fn finish(&mut self) -> Option<ByteOutput<'_>> {
+        truncate(&mut self.ready, 0);
+        mem::swap(&mut self.ready, &mut self.partial);
+
+        if self.ready.is_empty() {
+            None
+        } else {
+            Some(ByteOutput::TextBlock(&self.ready))
+        }
+    }
+
+    //Finishes the held back sequence or character with the start of `chunk`, returning what
+    //is left of `chunk`. Returns `None` if all of `chunk` was used without finishing it.
+    fn complete<'c>(&mut self, chunk: &'c [u8]) -> Option<&'c [u8]> {
+        let held = self.ready.len();
+
+        let fits = chunk.len().min(self.ready.capacity() - held);
+        //Can't fail, this is exactly the space that is left.
+        let _ = self.ready.extend_from_slice(&chunk[..fits]);
+
+        //`None` while it isn't finished yet, and shorter than what was held if it turns out not
+        //to be a sequence or character after all.
+        let end = if is_incomplete_bytes(&self.ready) {
+            None
+        } else if find_introducer(&self.ready) == Some(0) {
+            Some(parse_bytes(&self.ready).map_or(0, |(len, _)| len))
+        } else {
+            let width = utf8_width(self.ready[0]);
+            let cont = &self.ready[1..self.ready.len().min(width)];
+            if cont.iter().any(|byte| !(0x80..=0xbf).contains(byte)) {
+                Some(0)
+            } else if width <= self.ready.len() {
+                Some(width)
+            } else {
+                None
+            }
+        };
+
+        match end {
+            Some(end) if end >= held => {
+                truncate(&mut self.ready, end);
+                Some(&chunk[end - held..])
+            }
+            None if fits == chunk.len() => {
+                mem::swap(&mut self.ready, &mut self.partial);
+                None
+            }
+            //It no longer fits in the buffer, or isn't a sequence after all, so it gets passed
+            //on as it was.
+            _ => {
+                truncate(&mut self.ready, held);
+                Some(chunk)
+            }
+        }
+    }
+
+    //Holds on to a sequence or character that was cut off at the end of `chunk`, returning
+    //the part of `chunk` before it.
+    fn hold_tail<'c>(&mut self, chunk: &'c [u8]) -> &'c [u8] {
+        let text_end = split_char_start(chunk);
+
+        //Steps over whole sequences, as the inside of a string may look like an introducer.
+        let mut pos = 0;
+        let mut start = text_end;
+        while let Some(loc) = find_introducer(&chunk[pos..text_end]) {
+            let loc = pos + loc;
+            if is_incomplete_bytes(&chunk[loc..]) {
+                start = loc;
+                break;
+            }
+            pos = match parse_bytes(&chunk[loc..]) {
+                Some((len, _)) => loc + len,
+                None => loc + 1,
+            };
+        }
+
+        if start == chunk.len() || self.partial.extend_from_slice(&chunk[start..]).is_err() {
+            return chunk;
+        }
+
+        &chunk[..start]
+    }
+}
+
+impl<N: ArrayLength<u8>> Default for AnsiStreamParser<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///The complete output in the chunks fed to an `AnsiStreamParser`.
+#[derive(Debug)]
+pub struct AnsiStreamIterator<'a> {
+    ready: AnsiByteParseIterator<'a>,
+    chunk: AnsiByteParseIterator<'a>,
+}
+
+impl<'a> Iterator for AnsiStreamIterator<'a> {
+    type Item = ByteOutput<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ready.next().or_else(|| self.chunk.next())
+    }
+}
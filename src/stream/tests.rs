@@ -0,0 +1,197 @@
+use super::*;
+
+use crate::AnsiSequence;
+use heapless::consts::U8;
+use std::vec::Vec;
+
+//Writes output back as the bytes it was parsed from.
+fn bytes(out: &ByteOutput) -> Vec<u8> {
+    match out {
+        ByteOutput::TextBlock(text) => text.to_vec(),
+        ByteOutput::Escape(seq) => format!("{}", seq).into_bytes(),
+        ByteOutput::Unknown(seq) => format!("{}", seq).into_bytes(),
+    }
+}
+
+#[test]
+fn test_split_sequence() {
+    let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+
+    assert_eq!(
+        parser.feed(b"abc\x1b[3").collect::<Vec<_>>(),
+        vec![ByteOutput::TextBlock(b"abc")]
+    );
+    assert_eq!(
+        parser.feed(b"1mdef").collect::<Vec<_>>(),
+        vec![
+            ByteOutput::Escape(AnsiSequence::SetGraphicsMode(
+                heapless::Vec::from_slice(&[31.into()]).unwrap()
+            )),
+            ByteOutput::TextBlock(b"def"),
+        ]
+    );
+    assert_eq!(parser.finish(), None);
+}
+
+#[test]
+fn test_split_character() {
+    let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+
+    assert_eq!(
+        parser.feed(b"ab\xe2\x80").collect::<Vec<_>>(),
+        vec![ByteOutput::TextBlock(b"ab")]
+    );
+    assert_eq!(
+        parser.feed(b"\x94c").collect::<Vec<_>>(),
+        vec![
+            ByteOutput::TextBlock("—".as_bytes()),
+            ByteOutput::TextBlock(b"c")
+        ]
+    );
+    assert_eq!(parser.finish(), None);
+
+    // Not the rest of the character after all.
+    assert_eq!(parser.feed(b"\xe2").count(), 0);
+    assert_eq!(
+        parser.feed(b"\x1b[K").collect::<Vec<_>>(),
+        vec![
+            ByteOutput::TextBlock(b"\xe2"),
+            ByteOutput::Escape(AnsiSequence::EraseInLine(crate::EraseMode::ToEnd))
+        ]
+    );
+}
+
+#[test]
+fn test_matches_whole_input() {
+    let text = "a\x1b[1;31mbé\x1b]0;tïtle\x1b\\c\x1b[?1049h—\x1b(Bd\x1b[K🦀ef".as_bytes();
+    let whole: Vec<_> = text.ansi_parse().map(|o| bytes(&o)).collect();
+
+    // Split the text at every possible pair of positions, including inside characters.
+    for i in 0..=text.len() {
+        for j in i..=text.len() {
+            let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+            let mut rebuilt = Vec::new();
+            let mut escapes = Vec::new();
+            for chunk in &[&text[..i], &text[i..j], &text[j..]] {
+                for o in parser.feed(chunk) {
+                    if let ByteOutput::TextBlock(text) = o {
+                        assert!(core::str::from_utf8(text).is_ok(), "{} {}", i, j);
+                    } else {
+                        escapes.push(bytes(&o));
+                    }
+                    rebuilt.extend(bytes(&o));
+                }
+            }
+            assert_eq!(parser.finish(), None);
+            assert_eq!(rebuilt, text);
+
+            let expected: Vec<_> = whole.iter().filter(|o| o[0] == 0x1b).collect();
+            assert_eq!(escapes.iter().collect::<Vec<_>>(), expected, "{} {}", i, j);
+        }
+    }
+}
+
+#[test]
+fn test_finish_flushes_partial() {
+    let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+
+    assert_eq!(parser.feed(b"text\x1b]0;ti").count(), 1);
+    assert_eq!(parser.feed(b"tle").count(), 0);
+    assert_eq!(
+        parser.finish(),
+        Some(ByteOutput::TextBlock(b"\x1b]0;title"))
+    );
+    assert_eq!(parser.finish(), None);
+
+    assert_eq!(parser.feed(b"text\xf0\x9f").count(), 1);
+    assert_eq!(parser.finish(), Some(ByteOutput::TextBlock(b"\xf0\x9f")));
+}
+
+#[test]
+fn test_not_a_sequence() {
+    let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+
+    assert_eq!(parser.feed(b"\x1b[1").count(), 0);
+    assert_eq!(
+        parser.feed(b"\nx").collect::<Vec<_>>(),
+        vec![
+            ByteOutput::TextBlock(b"\x1b[1"),
+            ByteOutput::TextBlock(b"\nx")
+        ]
+    );
+}
+
+#[test]
+fn test_overflow() {
+    let mut parser: AnsiStreamParser<U8> = AnsiStreamParser::new();
+
+    // Too long to be held at all.
+    assert_eq!(
+        parser.feed(b"a\x1b]0;a long title").collect::<Vec<_>>(),
+        vec![
+            ByteOutput::TextBlock(b"a"),
+            ByteOutput::TextBlock(b"\x1b]0;a long title")
+        ]
+    );
+
+    // Held, but outgrows the buffer once more input arrives.
+    assert_eq!(parser.feed(b"\x1b]0;ab").count(), 0);
+    assert_eq!(
+        parser.feed(b"cdefgh\x07").collect::<Vec<_>>(),
+        vec![
+            ByteOutput::TextBlock(b"\x1b]0;ab"),
+            ByteOutput::TextBlock(b"cdefgh\x07")
+        ]
+    );
+}
+
+#[test]
+fn test_split_non_ascii_string() {
+    let text = "x\x1b]0;日本\x07y".as_bytes();
+
+    for i in 0..=text.len() {
+        let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+        let mut parsed: Vec<_> = parser.feed(&text[..i]).map(|o| bytes(&o)).collect();
+        parsed.extend(parser.feed(&text[i..]).map(|o| bytes(&o)));
+        assert_eq!(parser.finish(), None);
+
+        let escapes: Vec<_> = parsed.iter().filter(|o| o[0] == 0x1b).collect();
+        assert_eq!(escapes, ["\x1b]0;日本\x07".as_bytes()], "{}", i);
+        assert_eq!(parsed.concat(), text);
+    }
+}
+
+#[test]
+fn test_split_kitty_chunk() {
+    let mut chunk = b"\x1b_Gf=32,s=32,v=32,m=1;".to_vec();
+    chunk.extend_from_slice(&[b'A'; 4096]);
+    chunk.extend_from_slice(b"\x1b\\");
+
+    let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+    assert_eq!(parser.feed(&chunk[..1000]).count(), 0);
+    let parsed: Vec<_> = parser.feed(&chunk[1000..]).collect();
+    match parsed.as_slice() {
+        [ByteOutput::Escape(AnsiSequence::KittyGraphics(cmd, _))] => assert!(cmd.more()),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_unterminated_c1_string() {
+    let text = b"a\x90qabc";
+
+    // Parsed in one go, the string is text right away.
+    assert_eq!(
+        text.ansi_parse().last(),
+        Some(ByteOutput::TextBlock(b"\x90qabc"))
+    );
+
+    // Fed in, it is held until the input ends.
+    let mut parser: AnsiStreamParser = AnsiStreamParser::new();
+    assert_eq!(
+        parser.feed(text).collect::<Vec<_>>(),
+        vec![ByteOutput::TextBlock(b"a")]
+    );
+    assert_eq!(parser.feed(b"def").count(), 0);
+    assert_eq!(parser.finish(), Some(ByteOutput::TextBlock(b"\x90qabcdef")));
+}
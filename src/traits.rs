@@ -1,5 +1,5 @@
-use crate::enums::Output;
-use crate::parsers::parse_escape;
+use crate::enums::{ByteOutput, Output, Sequence};
+use crate::parsers::{interpret, parse_bytes, parse_escape, parse_sequence, utf8_width};
 
 pub trait AnsiParser {
     fn ansi_parse(&self) -> AnsiParseIterator<'_>;
@@ -20,7 +20,7 @@ impl AnsiParser for String {
 
 #[derive(Debug)]
 pub struct AnsiParseIterator<'a> {
-    dat: &'a str,
+    pub(crate) dat: &'a str,
 }
 
 impl<'a> Iterator for AnsiParseIterator<'a> {
@@ -34,9 +34,21 @@ impl<'a> Iterator for AnsiParseIterator<'a> {
         let pos = self.dat.find('\u{1b}');
         if let Some(loc) = pos {
             if loc == 0 {
-                let res = parse_escape(&self.dat[loc..]);
+                if let Ok((rest, seq)) = parse_sequence(self.dat) {
+                    let raw = &self.dat[..self.dat.len() - rest.len()];
+                    self.dat = rest;
 
-                if let Ok(ret) = res {
+                    //The sequence is delimited first, then interpreted on its own so that
+                    //an unrecognized sequence is never split in two.
+                    let body = match seq {
+                        Sequence::Csi(_) => &raw[2..],
+                        _ => &raw[1..],
+                    };
+                    match interpret(&seq, body) {
+                        Some(esc) => Some(Output::Escape(esc)),
+                        None => Some(Output::Unknown(seq)),
+                    }
+                } else if let Ok(ret) = parse_escape(self.dat) {
                     self.dat = ret.0;
                     Some(Output::Escape(ret.1))
                 } else {
@@ -69,3 +81,75 @@ impl<'a> Iterator for AnsiParseIterator<'a> {
         }
     }
 }
+
+pub trait AnsiByteParser {
+    fn ansi_parse(&self) -> AnsiByteParseIterator<'_>;
+}
+
+impl AnsiByteParser for [u8] {
+    fn ansi_parse(&self) -> AnsiByteParseIterator<'_> {
+        AnsiByteParseIterator { dat: self }
+    }
+}
+
+#[cfg(any(feature = "std", test))]
+impl AnsiByteParser for Vec<u8> {
+    fn ansi_parse(&self) -> AnsiByteParseIterator<'_> {
+        AnsiByteParseIterator { dat: self }
+    }
+}
+
+///Parses raw bytes, such as the output of a PTY, without decoding them first. Invalid UTF-8
+///is passed through inside of text blocks, and 8-bit C1 controls are read as the 7-bit escapes
+///they stand for.
+#[derive(Debug)]
+pub struct AnsiByteParseIterator<'a> {
+    pub(crate) dat: &'a [u8],
+}
+
+//Finds the next `ESC` or C1 control. Bytes in the C1 range that are part of a UTF-8 encoded
+//character are skipped over.
+pub(crate) fn find_introducer(dat: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i < dat.len() {
+        if matches!(dat[i], 0x1b | 0x80..=0x9f) {
+            return Some(i);
+        }
+        let width = utf8_width(dat[i]);
+
+        let valid = match dat.get(i..i + width) {
+            Some(c) => core::str::from_utf8(c).is_ok(),
+            None => false,
+        };
+        i += if valid { width } else { 1 };
+    }
+
+    None
+}
+
+impl<'a> Iterator for AnsiByteParseIterator<'a> {
+    type Item = ByteOutput<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.dat.is_empty() {
+            return None;
+        }
+
+        if let Some((len, out)) = parse_bytes(self.dat) {
+            self.dat = &self.dat[len..];
+            return Some(out);
+        }
+
+        //Either plain text, or an introducer that didn't lead to a valid sequence. Both are
+        //passed on as text up until the next introducer.
+        let loc = match find_introducer(self.dat) {
+            Some(0) => find_introducer(&self.dat[1..]).map(|loc| loc + 1),
+            loc => loc,
+        };
+        let loc = loc.unwrap_or(self.dat.len());
+        let temp = &self.dat[..loc];
+        self.dat = &self.dat[loc..];
+
+        Some(ByteOutput::TextBlock(temp))
+    }
+}